@@ -0,0 +1,80 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// Wrap a stream to check that its items are ordered according to `cmp`, passing them through
+/// unchanged.
+///
+/// Equal consecutive items are allowed, so this checks for a non-decreasing order.
+///
+/// # Panics
+/// The returned stream panics if an item compares as less than its predecessor.
+pub fn assert_ordered<S, F>(stream: S, cmp: F) -> AssertOrdered<S, F>
+    where S: Stream,
+          S::Item: Clone + Debug,
+          F: FnMut(&S::Item, &S::Item) -> Ordering
+{
+    AssertOrdered {
+        inner: stream,
+        cmp,
+        last: None,
+    }
+}
+
+/// The stream returned by `assert_ordered`.
+pub struct AssertOrdered<S: Stream, F> {
+    inner: S,
+    cmp: F,
+    last: Option<S::Item>,
+}
+
+impl<S, F> Stream for AssertOrdered<S, F>
+    where S: Stream,
+          S::Item: Clone + Debug,
+          F: FnMut(&S::Item, &S::Item) -> Ordering
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let item = match self.inner.poll_next(cx)? {
+            Async::Ready(Some(item)) => item,
+            other => return Ok(other),
+        };
+
+        if let Some(ref last) = self.last {
+            if (self.cmp)(last, &item) == Ordering::Greater {
+                panic!("Stream emitted {:?} after {:?}", item, last);
+            }
+        }
+        self.last = Some(item.clone());
+        Ok(Async::Ready(Some(item)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use iter_stream::IterStream;
+
+    #[test]
+    fn passes_ordered_items() {
+        let stream = IterStream::new(vec![Ok::<_, Never>(0), Ok(1), Ok(1), Ok(3)]);
+        let items = block_on(assert_ordered(stream, Ord::cmp).collect()).unwrap();
+        assert_eq!(items, vec![0, 1, 1, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stream emitted 1 after 2")]
+    fn detects_disorder() {
+        let stream = IterStream::new(vec![Ok::<_, Never>(0), Ok(2), Ok(1)]);
+        let _ = block_on(assert_ordered(stream, Ord::cmp).collect());
+    }
+}
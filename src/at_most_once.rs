@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// A stream adapter modelling lossy at-most-once delivery to a slow consumer.
+///
+/// Whenever it is polled, all items the inner stream has ready are moved into a buffer holding at
+/// most `buffer_bound` items. If the consumer does not keep up, the oldest buffered items are
+/// dropped to make room for newer ones, so the consumer always receives the freshest items
+/// instead of blocking the source. A single poll pulls at most `MAX_PULLS_PER_POLL` items.
+pub struct AtMostOnce<S: Stream> {
+    inner: S,
+    buffer_bound: usize,
+    buffer: VecDeque<S::Item>,
+    dropped: usize,
+    done: bool,
+}
+
+impl<S: Stream> AtMostOnce<S> {
+    /// Create a new `AtMostOnce`, buffering at most `buffer_bound` items.
+    ///
+    /// # Panics
+    /// Panics if `buffer_bound` is 0.
+    pub fn new(inner: S, buffer_bound: usize) -> AtMostOnce<S> {
+        if buffer_bound == 0 {
+            panic!("AtMostOnce must be able to buffer at least one item")
+        }
+
+        AtMostOnce {
+            inner,
+            buffer_bound,
+            buffer: VecDeque::new(),
+            dropped: 0,
+            done: false,
+        }
+    }
+
+    /// Return how many items have been dropped so far.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+impl<S: Stream> Stream for AtMostOnce<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut pulls = 0;
+        while !self.done && pulls < ::MAX_PULLS_PER_POLL {
+            pulls += 1;
+            match self.inner.poll_next(cx)? {
+                Async::Ready(Some(item)) => {
+                    if self.buffer.len() == self.buffer_bound {
+                        self.buffer.pop_front();
+                        self.dropped += 1;
+                    }
+                    self.buffer.push_back(item);
+                }
+                Async::Ready(None) => self.done = true,
+                Async::Pending => break,
+            }
+        }
+
+        match self.buffer.pop_front() {
+            Some(item) => Ok(Async::Ready(Some(item))),
+            None if self.done => Ok(Async::Ready(None)),
+            None => Ok(Async::Pending),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_sink::Sink;
+    use futures::StreamExt;
+    use futures::stream::repeat;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+    use test_channel::test_channel;
+
+    #[test]
+    fn slow_consumer_loses_oldest() {
+        let (mut sender, receiver) = test_channel::<u8, Never>(8);
+        let mut stream = AtMostOnce::new(receiver, 2);
+
+        with_noop_context(|cx| {
+            assert!(sender.poll_ready(cx).unwrap().is_ready());
+            sender.start_send(Ok(0)).unwrap();
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(0))));
+
+            for i in 1..6 {
+                assert!(sender.poll_ready(cx).unwrap().is_ready());
+                sender.start_send(Ok(i)).unwrap();
+            }
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(4))));
+            assert_eq!(stream.dropped(), 3);
+        });
+
+        drop(sender);
+        assert_eq!(block_on(stream.collect()).unwrap(), vec![5]);
+    }
+
+    #[test]
+    fn always_ready_source() {
+        let mut stream = AtMostOnce::new(repeat::<_, Never>(7), 2);
+        with_noop_context(|cx| {
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(7))));
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(7))));
+        });
+        assert!(stream.dropped() > 0);
+    }
+}
@@ -0,0 +1,244 @@
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+use futures_util::future::poll_fn;
+
+use step;
+
+#[derive(Default)]
+struct ProbeState {
+    accepted: usize,
+    forwarded: usize,
+    max_buffered: usize,
+}
+
+/// A sink that counts the items forwarded to it by a sink under test.
+///
+/// Together with the number of items the sink under test accepted, this yields the number of items
+/// the sink under test is currently buffering. See `assert_bounded_buffer`.
+pub struct BufferProbe<S> {
+    inner: S,
+    state: Rc<RefCell<ProbeState>>,
+}
+
+impl<S> BufferProbe<S> {
+    fn new(inner: S) -> BufferProbe<S> {
+        BufferProbe {
+            inner,
+            state: Rc::new(RefCell::new(ProbeState::default())),
+        }
+    }
+
+    /// Return the maximum number of items that have been buffered by the sink under test so far.
+    pub fn max_buffered(&self) -> usize {
+        self.state.borrow().max_buffered
+    }
+}
+
+impl<S: Sink> Sink for BufferProbe<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.inner.start_send(item)?;
+        self.state.borrow_mut().forwarded += 1;
+        Ok(())
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_close(cx)
+    }
+}
+
+/// Send all items of `input` into a sink and close it, asserting that the sink never buffers more
+/// than `bound` items at a time.
+///
+/// `sink_builder` is given a `BufferProbe` wrapping a `Vec` and must return the sink under test,
+/// which forwards into the probe. The sink is driven with the `step` executor. Returns the maximum
+/// number of items that were buffered at any time.
+///
+/// # Panics
+/// Panics if more than `bound` items were accepted but not yet forwarded, or if the sink errors.
+pub fn assert_bounded_buffer<T, S, B, I>(sink_builder: B, bound: usize, input: I) -> usize
+    where B: FnOnce(BufferProbe<Vec<T>>) -> S,
+          S: Sink<SinkItem = T>,
+          S::SinkError: Debug,
+          I: IntoIterator<Item = T>
+{
+    let probe = BufferProbe::new(Vec::new());
+    let state = probe.state.clone();
+    let mut sink = sink_builder(probe);
+    let mut input = input.into_iter().peekable();
+
+    let drive = poll_fn(|cx| -> Poll<(), S::SinkError> {
+        while input.peek().is_some() {
+            if let Async::Pending = sink.poll_ready(cx)? {
+                return Ok(Async::Pending);
+            }
+            sink.start_send(input.next().unwrap())?;
+
+            let mut state = state.borrow_mut();
+            state.accepted += 1;
+            let buffered = state.accepted - state.forwarded;
+            if buffered > bound {
+                panic!("Sink buffered {} items, exceeding the bound of {}",
+                       buffered,
+                       bound);
+            }
+            state.max_buffered = state.max_buffered.max(buffered);
+        }
+        sink.poll_close(cx)
+    });
+
+    if let Err(err) = step::run(drive) {
+        panic!("Sink under test emitted an error: {:?}", err);
+    }
+
+    let max_buffered = state.borrow().max_buffered;
+    max_buffered
+}
+
+/// Send all items of `input` into a sink without ever flushing it, then close it, asserting that
+/// all items were forwarded by the time closing completes.
+///
+/// `sink_builder` is given a `BufferProbe` wrapping a `Vec` and must return the sink under test,
+/// which forwards into the probe. The sink is driven with the `step` executor.
+///
+/// # Panics
+/// Panics if closing completes before all items were forwarded, or if the sink errors.
+pub fn assert_close_flushes<T, S, B, I>(sink_builder: B, input: I)
+    where B: FnOnce(BufferProbe<Vec<T>>) -> S,
+          S: Sink<SinkItem = T>,
+          S::SinkError: Debug,
+          I: IntoIterator<Item = T>
+{
+    let probe = BufferProbe::new(Vec::new());
+    let state = probe.state.clone();
+    let mut sink = sink_builder(probe);
+    let mut input = input.into_iter().peekable();
+    let mut sent = 0;
+
+    let drive = poll_fn(|cx| -> Poll<(), S::SinkError> {
+        while input.peek().is_some() {
+            if let Async::Pending = sink.poll_ready(cx)? {
+                return Ok(Async::Pending);
+            }
+            sink.start_send(input.next().unwrap())?;
+            sent += 1;
+        }
+        sink.poll_close(cx)
+    });
+
+    if let Err(err) = step::run(drive) {
+        panic!("Sink under test emitted an error: {:?}", err);
+    }
+
+    let forwarded = state.borrow().forwarded;
+    if forwarded != sent {
+        panic!("Sink forwarded only {} of {} items by the time it was closed",
+               forwarded,
+               sent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Forwards items to the inner sink in batches of `n`.
+    struct Chunk<S: Sink> {
+        inner: S,
+        n: usize,
+        buffer: Vec<S::SinkItem>,
+        flush_on_close: bool,
+    }
+
+    impl<S: Sink> Chunk<S> {
+        fn new(inner: S, n: usize) -> Chunk<S> {
+            Chunk {
+                inner,
+                n,
+                buffer: Vec::new(),
+                flush_on_close: true,
+            }
+        }
+
+        fn forward_buffer(&mut self) -> Result<(), S::SinkError> {
+            for item in self.buffer.drain(..) {
+                self.inner.start_send(item)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<S: Sink> Sink for Chunk<S> {
+        type SinkItem = S::SinkItem;
+        type SinkError = S::SinkError;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.buffer.len() == self.n {
+                self.forward_buffer()?;
+            }
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.buffer.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.forward_buffer()?;
+            self.inner.poll_flush(cx)
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.flush_on_close {
+                self.forward_buffer()?;
+            } else {
+                self.buffer.clear();
+            }
+            self.inner.poll_close(cx)
+        }
+    }
+
+    #[test]
+    fn chunk_buffers_batch_size() {
+        let max = assert_bounded_buffer(|probe| Chunk::new(probe, 3), 3, 0..10);
+        assert_eq!(max, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeding the bound of 2")]
+    fn chunk_exceeds_smaller_bound() {
+        assert_bounded_buffer(|probe| Chunk::new(probe, 3), 2, 0..10);
+    }
+
+    #[test]
+    fn close_flushes_chunk() {
+        assert_close_flushes(|probe| Chunk::new(probe, 3), 0..10);
+    }
+
+    #[test]
+    #[should_panic(expected = "by the time it was closed")]
+    fn close_drops_chunk() {
+        assert_close_flushes(|probe| {
+                                 let mut chunk = Chunk::new(probe, 3);
+                                 chunk.flush_on_close = false;
+                                 chunk
+                             },
+                             0..10);
+    }
+}
@@ -0,0 +1,232 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+#[derive(Default)]
+struct Rounds {
+    stages: usize,
+    // The lowest stage that reported `Pending` from `poll_ready` since the head was last polled.
+    lowest_pending: Option<usize>,
+    pending_rounds: usize,
+}
+
+/// Verifies that backpressure propagates through a chain of sink stages.
+///
+/// Each stage of the chain is wrapped via `stage`, starting at the tail of the chain and ending
+/// with its head. Every call to the head's `poll_ready` forms a round. If any stage reports
+/// `Pending` from `poll_ready` during a round, the head must report `Pending` in the same round as
+/// well. Only the head is checked: The stages in between may report `Ready` in such a round, as
+/// long as the head reports `Pending`.
+///
+/// # Panics
+/// The head panics if it reports `Ready` in a round in which a lower stage reported `Pending`.
+#[derive(Default)]
+pub struct ChainProbe(Rc<RefCell<Rounds>>);
+
+impl ChainProbe {
+    /// Create a new `ChainProbe` without any stages.
+    pub fn new() -> ChainProbe {
+        ChainProbe::default()
+    }
+
+    /// Wrap the next stage of the chain, whose inner sink is the previously wrapped stage.
+    ///
+    /// The most recently wrapped stage is considered the head of the chain.
+    pub fn stage<S: Sink>(&self, sink: S) -> ProbedStage<S> {
+        let mut rounds = self.0.borrow_mut();
+        rounds.stages += 1;
+        ProbedStage {
+            inner: sink,
+            index: rounds.stages - 1,
+            rounds: self.0.clone(),
+        }
+    }
+
+    /// Return in how many rounds the head of the chain reported `Pending`.
+    pub fn pending_rounds(&self) -> usize {
+        self.0.borrow().pending_rounds
+    }
+}
+
+/// A stage of a chain of sinks, observed by a `ChainProbe`.
+pub struct ProbedStage<S> {
+    inner: S,
+    index: usize,
+    rounds: Rc<RefCell<Rounds>>,
+}
+
+impl<S> ProbedStage<S> {
+    /// Consume the `ProbedStage`, returning the wrapped sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Sink> Sink for ProbedStage<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        let result = self.inner.poll_ready(cx)?;
+
+        let mut rounds = self.rounds.borrow_mut();
+        let is_head = self.index + 1 == rounds.stages;
+
+        if let Async::Pending = result {
+            if rounds.lowest_pending.is_none() {
+                rounds.lowest_pending = Some(self.index);
+            }
+        }
+
+        if is_head {
+            let lowest_pending = rounds.lowest_pending.take();
+            match (result, lowest_pending) {
+                (Async::Ready(()), Some(stage)) => {
+                    panic!("Stage {} reported Pending, but the head of the chain reported Ready",
+                           stage)
+                }
+                (Async::Pending, _) => rounds.pending_rounds += 1,
+                _ => {}
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.inner.start_send(item)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use futures::never::Never;
+
+    use step::with_noop_context;
+
+    // A sink that is pending whenever `blocked` is set.
+    struct Slow {
+        blocked: Rc<Cell<bool>>,
+    }
+
+    impl Sink for Slow {
+        type SinkItem = Vec<u8>;
+        type SinkError = Never;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.blocked.get() {
+                Ok(Async::Pending)
+            } else {
+                Ok(Async::Ready(()))
+            }
+        }
+
+        fn start_send(&mut self, _: Self::SinkItem) -> Result<(), Self::SinkError> {
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    // Forwards chunks of two items, only propagating backpressure if `propagate` is set.
+    struct Chunk<S> {
+        inner: S,
+        buffer: Vec<u8>,
+        propagate: bool,
+    }
+
+    impl<S: Sink<SinkItem = Vec<u8>>> Sink for Chunk<S> {
+        type SinkItem = u8;
+        type SinkError = S::SinkError;
+
+        fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.buffer.len() < 2 {
+                return Ok(Async::Ready(()));
+            }
+
+            match self.inner.poll_ready(cx)? {
+                Async::Ready(()) => {
+                    let chunk = self.buffer.split_off(0);
+                    self.inner.start_send(chunk)?;
+                    Ok(Async::Ready(()))
+                }
+                Async::Pending if self.propagate => Ok(Async::Pending),
+                Async::Pending => {
+                    self.buffer.clear();
+                    Ok(Async::Ready(()))
+                }
+            }
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.buffer.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.inner.poll_flush(cx)
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.inner.poll_close(cx)
+        }
+    }
+
+    fn drive(propagate: bool) -> ChainProbe {
+        let blocked = Rc::new(Cell::new(false));
+        let probe = ChainProbe::new();
+        let tail = probe.stage(Slow { blocked: blocked.clone() });
+        let mut head = probe.stage(Chunk {
+                                       inner: tail,
+                                       buffer: Vec::new(),
+                                       propagate,
+                                   });
+
+        with_noop_context(|cx| {
+            for i in 0..2 {
+                assert!(head.poll_ready(cx).unwrap().is_ready());
+                head.start_send(i).unwrap();
+            }
+
+            blocked.set(true);
+            assert!(head.poll_ready(cx).unwrap().is_pending());
+
+            blocked.set(false);
+            assert!(head.poll_ready(cx).unwrap().is_ready());
+        });
+
+        probe
+    }
+
+    #[test]
+    fn backpressure_propagates() {
+        assert_eq!(drive(true).pending_rounds(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "but the head of the chain reported Ready")]
+    fn swallowed_backpressure_panics() {
+        drive(false);
+    }
+}
@@ -0,0 +1,121 @@
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink adapter that forwards items to the inner sink in chunks of a fixed size.
+///
+/// A chunk is emitted as soon as it is full. Flushing or closing also emits a partial chunk of the
+/// items buffered so far, so that no items are held back.
+pub struct ChunkSink<S, T> {
+    inner: S,
+    chunk_size: usize,
+    buffer: Vec<T>,
+    // A chunk that has not been accepted by the inner sink yet.
+    pending: Option<Vec<T>>,
+}
+
+impl<S, T> ChunkSink<S, T>
+    where S: Sink<SinkItem = Vec<T>>
+{
+    /// Create a new `ChunkSink`, emitting chunks of `chunk_size` items.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    pub fn new(inner: S, chunk_size: usize) -> ChunkSink<S, T> {
+        if chunk_size == 0 {
+            panic!("ChunkSink must have a chunk size greater than 0")
+        }
+
+        ChunkSink {
+            inner,
+            chunk_size,
+            buffer: Vec::with_capacity(chunk_size),
+            pending: None,
+        }
+    }
+
+    /// Consume the `ChunkSink`, returning the inner sink. Buffered items are lost.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    // Take the buffered items, leaving an empty buffer that can hold a full chunk.
+    fn take_chunk(&mut self) -> Vec<T> {
+        ::std::mem::replace(&mut self.buffer, Vec::with_capacity(self.chunk_size))
+    }
+
+    // Hand the pending chunk to the inner sink.
+    fn poll_pending(&mut self, cx: &mut Context) -> Poll<(), S::SinkError> {
+        if let Some(chunk) = self.pending.take() {
+            if let Async::Pending = self.inner.poll_ready(cx)? {
+                self.pending = Some(chunk);
+                return Ok(Async::Pending);
+            }
+            self.inner.start_send(chunk)?;
+        }
+        Ok(Async::Ready(()))
+    }
+
+    // Hand the pending chunk and then all buffered items to the inner sink.
+    fn poll_emit_all(&mut self, cx: &mut Context) -> Poll<(), S::SinkError> {
+        if let Async::Pending = self.poll_pending(cx)? {
+            return Ok(Async::Pending);
+        }
+        if !self.buffer.is_empty() {
+            self.pending = Some(self.take_chunk());
+            return self.poll_pending(cx);
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<S, T> Sink for ChunkSink<S, T>
+    where S: Sink<SinkItem = Vec<T>>
+{
+    type SinkItem = T;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.poll_pending(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.buffer.push(item);
+        if self.buffer.len() == self.chunk_size {
+            self.pending = Some(self.take_chunk());
+        }
+        Ok(())
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.poll_emit_all(cx)? {
+            return Ok(Async::Pending);
+        }
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.poll_emit_all(cx)? {
+            return Ok(Async::Pending);
+        }
+        self.inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+
+    use send_all_close::SendAllClose;
+    use vec_sink::VecSink;
+
+    #[test]
+    fn emits_full_and_partial_chunks() {
+        let sink = ChunkSink::new(VecSink::new(), 2);
+        let sink = block_on(SendAllClose::new(sink, 0..5)).unwrap();
+        assert_eq!(sink.into_inner().into_vec(),
+                   vec![vec![0, 1], vec![2, 3], vec![4]]);
+    }
+}
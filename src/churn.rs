@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+
+use futures_core::{Poll, Async};
+use futures_sink::Sink;
+use futures_util::future::poll_fn;
+
+use step;
+
+/// Repeatedly build a fresh sink, send a batch of items into it and close it, asserting that each
+/// cycle completes cleanly.
+///
+/// In each of the `cycles` cycles, a sink is created via `sink_factory`, the items
+/// `0..items_per_cycle` are sent into it and it is closed, driven with the `step` executor. A
+/// failing cycle does not stop the test, the outcomes of all cycles are collected first. Returns
+/// the closed sinks of all cycles, in order, for inspecting their final states.
+///
+/// # Panics
+/// Panics after all cycles ran if the sink of any cycle errored, reporting the number and the error
+/// of each failed cycle.
+pub fn churn_test<S, F>(mut sink_factory: F, cycles: usize, items_per_cycle: usize) -> Vec<S>
+    where F: FnMut() -> S,
+          S: Sink<SinkItem = usize>,
+          S::SinkError: Debug
+{
+    let mut sinks = Vec::with_capacity(cycles);
+    let mut failures = Vec::new();
+
+    for cycle in 0..cycles {
+        let mut sink = sink_factory();
+        let mut next = 0;
+
+        let drive = poll_fn(|cx| -> Poll<(), S::SinkError> {
+            while next < items_per_cycle {
+                if let Async::Pending = sink.poll_ready(cx)? {
+                    return Ok(Async::Pending);
+                }
+                sink.start_send(next)?;
+                next += 1;
+            }
+            sink.poll_close(cx)
+        });
+
+        match step::run(drive) {
+            Ok(()) => sinks.push(sink),
+            Err(err) => failures.push(format!("cycle {}: {:?}", cycle, err)),
+        }
+    }
+
+    if !failures.is_empty() {
+        panic!("Sinks of {} of {} cycles emitted an error ({})",
+               failures.len(),
+               cycles,
+               failures.join(", "));
+    }
+
+    sinks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_core::task::Context;
+
+    use vec_sink::VecSink;
+
+    #[test]
+    fn vec_cycles_complete() {
+        let sinks = churn_test(VecSink::new, 5, 3);
+        assert_eq!(sinks.len(), 5);
+        assert_eq!(sinks.iter().map(|sink| sink.items().len()).sum::<usize>(), 5 * 3);
+        assert!(sinks.iter().all(|sink| sink.items() == [0, 1, 2]));
+    }
+
+    // Fails to close if `failing` is set.
+    struct FailingClose {
+        failing: bool,
+    }
+
+    impl Sink for FailingClose {
+        type SinkItem = usize;
+        type SinkError = &'static str;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, _: Self::SinkItem) -> Result<(), Self::SinkError> {
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.failing {
+                Err("leaked")
+            } else {
+                Ok(Async::Ready(()))
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Sinks of 2 of 4 cycles emitted an error (cycle 1: \"leaked\", \
+                               cycle 3: \"leaked\")")]
+    fn reports_all_failed_cycles() {
+        let mut cycle = 0;
+        churn_test(|| {
+                       cycle += 1;
+                       FailingClose { failing: cycle % 2 == 0 }
+                   },
+                   4,
+                   2);
+    }
+}
@@ -0,0 +1,180 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures_core::{Stream, Poll};
+use futures_core::task::{Context, Waker};
+use futures_sink::Sink;
+
+struct ClockState {
+    now: u64,
+    wakers: Vec<Waker>,
+}
+
+/// A shared logical clock, advanced explicitly by test code.
+///
+/// Time-dependent combinators such as `Debounce` can read the time from a `LogicalClock` instead
+/// of counting polls, so tests can advance time directly rather than polling repeatedly. Clones
+/// share the same time.
+#[derive(Clone)]
+pub struct LogicalClock(Rc<RefCell<ClockState>>);
+
+impl LogicalClock {
+    /// Create a new `LogicalClock` starting at time 0.
+    pub fn new() -> LogicalClock {
+        LogicalClock(Rc::new(RefCell::new(ClockState {
+                                              now: 0,
+                                              wakers: Vec::new(),
+                                          })))
+    }
+
+    /// Return the current logical time.
+    pub fn now(&self) -> u64 {
+        self.0.borrow().now
+    }
+
+    /// Advance the time by `n`, waking all tasks waiting on the clock.
+    pub fn advance(&self, n: u64) {
+        let wakers = {
+            let mut state = self.0.borrow_mut();
+            state.now += n;
+            ::std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Wake the current task the next time the clock is advanced.
+    ///
+    /// Registering the same task repeatedly before the clock advances wakes it only once.
+    pub fn wake_on_advance(&self, cx: &mut Context) {
+        let waker = cx.waker();
+        let wakers = &mut self.0.borrow_mut().wakers;
+        if !wakers.iter().any(|registered| registered.will_wake(&waker)) {
+            wakers.push(waker);
+        }
+    }
+}
+
+impl Default for LogicalClock {
+    fn default() -> LogicalClock {
+        LogicalClock::new()
+    }
+}
+
+/// A stream wrapper that stamps each item with the logical time at which it was yielded.
+pub struct ClockStream<S> {
+    inner: S,
+    clock: LogicalClock,
+}
+
+impl<S: Stream> ClockStream<S> {
+    /// Create a new `ClockStream`, reading the time from the given clock.
+    pub fn new(inner: S, clock: LogicalClock) -> ClockStream<S> {
+        ClockStream { inner, clock }
+    }
+
+    /// Return the current logical time.
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+}
+
+impl<S: Stream> Stream for ClockStream<S> {
+    type Item = (u64, S::Item);
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let now = self.clock.now();
+        self.inner
+            .poll_next(cx)
+            .map(|poll| poll.map(|item| item.map(|item| (now, item))))
+    }
+}
+
+/// A sink wrapper that stamps each item with the logical time at which it was sent.
+pub struct ClockSink<S> {
+    inner: S,
+    clock: LogicalClock,
+}
+
+impl<S: Sink> ClockSink<S> {
+    /// Create a new `ClockSink`, reading the time from the given clock.
+    pub fn new(inner: S, clock: LogicalClock) -> ClockSink<S> {
+        ClockSink { inner, clock }
+    }
+
+    /// Return the current logical time.
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+
+    /// Consume the `ClockSink`, returning the wrapped sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<T, S> Sink for ClockSink<S>
+    where S: Sink<SinkItem = (u64, T)>
+{
+    type SinkItem = T;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.inner.start_send((self.clock.now(), item))
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, StreamExt};
+    use futures::stream::iter_ok;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+
+    #[test]
+    fn stamps_items() {
+        let clock = LogicalClock::new();
+        let mut sink = ClockSink::new(Vec::new(), clock.clone());
+
+        sink = block_on(sink.send(0)).unwrap();
+        clock.advance(3);
+        sink = block_on(sink.send(1)).unwrap();
+        assert_eq!(sink.into_inner(), vec![(0, 0), (3, 1)]);
+
+        let stream = ClockStream::new(iter_ok::<_, Never>(vec![2]), clock.clone());
+        assert_eq!(block_on(stream.collect()).unwrap(), vec![(3, 2)]);
+    }
+
+    #[test]
+    fn registers_task_once() {
+        let clock = LogicalClock::new();
+
+        with_noop_context(|cx| {
+            for _ in 0..3 {
+                clock.wake_on_advance(cx);
+            }
+        });
+        assert_eq!(clock.0.borrow().wakers.len(), 1);
+
+        clock.advance(1);
+        assert!(clock.0.borrow().wakers.is_empty());
+    }
+}
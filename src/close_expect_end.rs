@@ -0,0 +1,102 @@
+use futures_core::{Future, Stream, Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// The error type of a `CloseExpectEnd` future.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseExpectEndError<I, SkE, StE> {
+    /// The stream emitted an item after closing the sink began.
+    Unexpected(I),
+    /// The sink emitted an error.
+    Sink(SkE),
+    /// The stream emitted an error.
+    Stream(StE),
+}
+
+/// Future which closes a sink while checking that a stream ends without emitting any further
+/// items, for example the two halves of a connection that is being shut down.
+///
+/// Both halves are polled whenever the future is polled. It completes once the sink has been
+/// closed and the stream has ended, yielding both of them, and errors if the stream emits an item.
+pub struct CloseExpectEnd<Sk, St> {
+    halves: Option<(Sk, St)>,
+    sink_done: bool,
+    stream_done: bool,
+}
+
+impl<Sk: Sink, St: Stream> CloseExpectEnd<Sk, St> {
+    /// Create a new `CloseExpectEnd` future closing `sink` and expecting `stream` to end.
+    pub fn new(sink: Sk, stream: St) -> CloseExpectEnd<Sk, St> {
+        CloseExpectEnd {
+            halves: Some((sink, stream)),
+            sink_done: false,
+            stream_done: false,
+        }
+    }
+}
+
+impl<Sk: Sink, St: Stream> Future for CloseExpectEnd<Sk, St> {
+    type Item = (Sk, St);
+    type Error = CloseExpectEndError<St::Item, Sk::SinkError, St::Error>;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<Self::Item, Self::Error> {
+        {
+            let (ref mut sink, ref mut stream) = *self.halves
+                .as_mut()
+                .expect("Attempted to poll CloseExpectEnd after completion");
+
+            if !self.sink_done {
+                if let Async::Ready(()) = sink.poll_close(cx).map_err(CloseExpectEndError::Sink)? {
+                    self.sink_done = true;
+                }
+            }
+
+            if !self.stream_done {
+                match stream.poll_next(cx).map_err(CloseExpectEndError::Stream)? {
+                    Async::Ready(Some(item)) => return Err(CloseExpectEndError::Unexpected(item)),
+                    Async::Ready(None) => self.stream_done = true,
+                    Async::Pending => {}
+                }
+            }
+
+            if !(self.sink_done && self.stream_done) {
+                return Ok(Async::Pending);
+            }
+        }
+
+        Ok(Async::Ready(self.halves.take().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::FutureExt;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use test_channel::test_duplex;
+
+    #[test]
+    fn half_duplex_shutdown() {
+        let (a, b) = test_duplex::<u8, Never>(1);
+        let (sender_a, receiver_a) = a.split();
+        let (sender_b, receiver_b) = b.split();
+
+        let close_a = CloseExpectEnd::new(sender_a, receiver_a);
+        let close_b = CloseExpectEnd::new(sender_b, receiver_b);
+        block_on(close_a.join(close_b)).unwrap();
+    }
+
+    #[test]
+    fn reports_unexpected_item() {
+        let (a, b) = test_duplex::<u8, Never>(1);
+        let (mut sender_b, _receiver_b) = b.split();
+        sender_b.try_send(Ok(7)).unwrap();
+
+        let (sender_a, receiver_a) = a.split();
+        assert_eq!(block_on(CloseExpectEnd::new(sender_a, receiver_a)).err(),
+                   Some(CloseExpectEndError::Unexpected(7)));
+    }
+}
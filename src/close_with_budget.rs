@@ -0,0 +1,108 @@
+use futures_core::{Future, Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// The error type of a `CloseWithBudget` future.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseWithBudgetError<E> {
+    /// Closing the sink returned `Pending` more often than allowed. Contains the number of times it
+    /// did.
+    CloseBudgetExceeded(usize),
+    /// The sink emitted an error.
+    Sink(E),
+}
+
+/// Future which closes a sink, but errors instead of waiting forever if closing does not complete.
+///
+/// Closing may return `Pending` at most `max_pending_polls` times, the next `Pending` results in an
+/// error. This turns a sink that hangs on close into a test failure rather than a stuck executor.
+pub struct CloseWithBudget<S> {
+    sink: Option<S>,
+    max_pending_polls: usize,
+    pending_polls: usize,
+}
+
+impl<S: Sink> CloseWithBudget<S> {
+    /// Create a new `CloseWithBudget` future that closes the given sink, tolerating at most
+    /// `max_pending_polls` pending results.
+    pub fn new(sink: S, max_pending_polls: usize) -> CloseWithBudget<S> {
+        CloseWithBudget {
+            sink: Some(sink),
+            max_pending_polls,
+            pending_polls: 0,
+        }
+    }
+}
+
+impl<S: Sink> Future for CloseWithBudget<S> {
+    type Item = S;
+    type Error = CloseWithBudgetError<S::SinkError>;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<Self::Item, Self::Error> {
+        let mut sink = self.sink
+            .take()
+            .expect("Attempted to poll CloseWithBudget after completion");
+
+        match sink.poll_close(cx).map_err(CloseWithBudgetError::Sink)? {
+            Async::Ready(()) => Ok(Async::Ready(sink)),
+            Async::Pending => {
+                self.pending_polls += 1;
+                if self.pending_polls > self.max_pending_polls {
+                    return Err(CloseWithBudgetError::CloseBudgetExceeded(self.pending_polls));
+                }
+                self.sink = Some(sink);
+                Ok(Async::Pending)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    // Becomes closed after being polled `pending` times.
+    struct SlowClose {
+        pending: usize,
+    }
+
+    impl Sink for SlowClose {
+        type SinkItem = ();
+        type SinkError = Never;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, _: Self::SinkItem) -> Result<(), Self::SinkError> {
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.pending == 0 {
+                return Ok(Async::Ready(()));
+            }
+            self.pending -= 1;
+            cx.waker().wake();
+            Ok(Async::Pending)
+        }
+    }
+
+    #[test]
+    fn within_budget() {
+        assert!(block_on(CloseWithBudget::new(SlowClose { pending: 3 }, 3)).is_ok());
+    }
+
+    #[test]
+    fn budget_exceeded() {
+        assert_eq!(block_on(CloseWithBudget::new(SlowClose { pending: 4 }, 3)).err(),
+                   Some(CloseWithBudgetError::CloseBudgetExceeded(4)));
+    }
+}
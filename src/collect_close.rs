@@ -0,0 +1,91 @@
+use futures_core::{Future, Stream, Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// The error type of a `CollectClose` future.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollectCloseError<StE, SkE> {
+    /// The stream emitted an error.
+    Stream(StE),
+    /// The sink emitted an error.
+    Sink(SkE),
+}
+
+/// Future which closes a sink while collecting all items of a stream, for example the two halves
+/// of a duplex connection.
+///
+/// Both halves are polled whenever the future is polled. It completes once the sink has been
+/// closed and the stream has ended, yielding the collected items and the sink.
+pub struct CollectClose<St: Stream, Sk> {
+    stream: St,
+    sink: Option<Sk>,
+    items: Vec<St::Item>,
+    stream_done: bool,
+    sink_done: bool,
+}
+
+impl<St: Stream, Sk: Sink> CollectClose<St, Sk> {
+    /// Create a new `CollectClose` future collecting `stream` and closing `sink`.
+    pub fn new(stream: St, sink: Sk) -> CollectClose<St, Sk> {
+        CollectClose {
+            stream,
+            sink: Some(sink),
+            items: Vec::new(),
+            stream_done: false,
+            sink_done: false,
+        }
+    }
+}
+
+impl<St: Stream, Sk: Sink> Future for CollectClose<St, Sk> {
+    type Item = (Vec<St::Item>, Sk);
+    type Error = CollectCloseError<St::Error, Sk::SinkError>;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<Self::Item, Self::Error> {
+        if !self.sink_done {
+            let sink = self.sink
+                .as_mut()
+                .expect("Attempted to poll CollectClose after completion");
+            if let Async::Ready(()) = sink.poll_close(cx).map_err(CollectCloseError::Sink)? {
+                self.sink_done = true;
+            }
+        }
+
+        while !self.stream_done {
+            match self.stream.poll_next(cx).map_err(CollectCloseError::Stream)? {
+                Async::Ready(Some(item)) => self.items.push(item),
+                Async::Ready(None) => self.stream_done = true,
+                Async::Pending => break,
+            }
+        }
+
+        if self.sink_done && self.stream_done {
+            let sink = self.sink
+                .take()
+                .expect("Attempted to poll CollectClose after completion");
+            Ok(Async::Ready((::std::mem::take(&mut self.items), sink)))
+        } else {
+            Ok(Async::Pending)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use test_channel::test_channel;
+
+    #[test]
+    fn closes_while_collecting() {
+        let (sender, receiver) = test_channel::<u8, Never>(4);
+        let sender = block_on(sender.send(Ok(0)).and_then(|sender| sender.send(Ok(1)))).unwrap();
+
+        let (items, _) = block_on(CollectClose::new(receiver, sender)).unwrap();
+        assert_eq!(items, vec![0, 1]);
+    }
+}
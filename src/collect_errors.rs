@@ -0,0 +1,139 @@
+use futures_core::{Poll, Async, Never};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink wrapper that records the errors of the inner sink instead of propagating them.
+///
+/// If `poll_ready` errors, the sink reports itself as ready anyway and the next item is dropped
+/// rather than being sent to the inner sink. Errors of `start_send` drop the item as well, errors
+/// of `poll_flush` and `poll_close` are treated as completion.
+pub struct CollectErrors<S: Sink> {
+    inner: S,
+    errors: Vec<S::SinkError>,
+    skip_next: bool,
+}
+
+impl<S: Sink> CollectErrors<S> {
+    /// Create a new `CollectErrors` wrapping the given sink.
+    pub fn new(inner: S) -> CollectErrors<S> {
+        CollectErrors {
+            inner,
+            errors: Vec::new(),
+            skip_next: false,
+        }
+    }
+
+    /// Return all errors emitted by the inner sink so far.
+    pub fn errors(&self) -> &[S::SinkError] {
+        &self.errors
+    }
+
+    /// Consume the `CollectErrors`, returning the wrapped sink and the collected errors.
+    pub fn into_inner(self) -> (S, Vec<S::SinkError>) {
+        (self.inner, self.errors)
+    }
+
+    fn record(&mut self, result: Poll<(), S::SinkError>) -> Poll<(), Never> {
+        match result {
+            Ok(poll) => Ok(poll),
+            Err(err) => {
+                self.errors.push(err);
+                Ok(Async::Ready(()))
+            }
+        }
+    }
+}
+
+impl<S: Sink> Sink for CollectErrors<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = Never;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if self.skip_next {
+            return Ok(Async::Ready(()));
+        }
+
+        match self.inner.poll_ready(cx) {
+            Ok(poll) => Ok(poll),
+            Err(err) => {
+                self.errors.push(err);
+                self.skip_next = true;
+                Ok(Async::Ready(()))
+            }
+        }
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        if self.skip_next {
+            self.skip_next = false;
+            return Ok(());
+        }
+
+        if let Err(err) = self.inner.start_send(item) {
+            self.errors.push(err);
+        }
+        Ok(())
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        let result = self.inner.poll_flush(cx);
+        self.record(result)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        let result = self.inner.poll_close(cx);
+        self.record(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::stream::iter_ok;
+    use futures::sink::close;
+    use futures::executor::block_on;
+
+    // Rejects every odd item on `start_send`.
+    struct RejectOdd(Vec<u8>);
+
+    impl Sink for RejectOdd {
+        type SinkItem = u8;
+        type SinkError = u8;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            if item % 2 == 1 {
+                Err(item)
+            } else {
+                self.0.push(item);
+                Ok(())
+            }
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    #[test]
+    fn collects_errors_and_continues() {
+        let sink = CollectErrors::new(RejectOdd(Vec::new()));
+
+        let sink = block_on(sink.send_all(iter_ok(0..6))
+                                .and_then(|(sink, _)| close(sink)))
+                .unwrap();
+        assert_eq!(sink.errors(), &[1, 3, 5]);
+
+        let (inner, _) = sink.into_inner();
+        assert_eq!(inner.0, vec![0, 2, 4]);
+    }
+}
@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use futures_core::{Stream, Poll, Async, Never};
+use futures_core::task::{Context, Waker};
+
+/// An entry appended to a `CompactedTopic`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopicEntry<I, K> {
+    /// A new value for the key of the item.
+    Value(I),
+    /// A marker deleting the given key.
+    Tombstone(K),
+}
+
+struct Topic<I, K, F> {
+    slots: Vec<Option<I>>,
+    index: HashMap<K, usize>,
+    key_fn: F,
+    offset: usize,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<I, K, F> Topic<I, K, F>
+    where K: Eq + Hash,
+          F: FnMut(&I) -> K
+{
+    fn append(&mut self, entry: TopicEntry<I, K>) {
+        match entry {
+            TopicEntry::Value(item) => {
+                let key = (self.key_fn)(&item);
+                if let Some(old) = self.index.insert(key, self.slots.len()) {
+                    self.slots[old] = None;
+                }
+                self.slots.push(Some(item));
+            }
+            TopicEntry::Tombstone(key) => {
+                if let Some(old) = self.index.remove(&key) {
+                    self.slots[old] = None;
+                }
+            }
+        }
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A stream reading a simulated compacted topic, which is appended to through a `TopicHandle`.
+///
+/// The key of each value is computed by `F`. Appending a value for a key removes the previous
+/// value for that key from the topic, appending a tombstone for a key removes its value without
+/// replacement. The stream yields (clones of) the values that remain in the topic, in the order
+/// they were appended. Once it has read all of them, the stream returns `Pending` until new values
+/// are appended. It ends when the handle has been dropped and all values were yielded.
+pub struct CompactedTopic<I, K, F>(Rc<RefCell<Topic<I, K, F>>>);
+
+impl<I, K, F> CompactedTopic<I, K, F>
+    where I: Clone,
+          K: Eq + Hash,
+          F: FnMut(&I) -> K
+{
+    /// Create a new `CompactedTopic` containing the given entries, using `key_fn` to compute the
+    /// key of each value, together with its handle.
+    pub fn new(initial: Vec<TopicEntry<I, K>>,
+               key_fn: F)
+               -> (CompactedTopic<I, K, F>, TopicHandle<I, K, F>) {
+        let mut topic = Topic {
+            slots: Vec::new(),
+            index: HashMap::new(),
+            key_fn,
+            offset: 0,
+            closed: false,
+            waker: None,
+        };
+        for entry in initial {
+            topic.append(entry);
+        }
+
+        let topic = Rc::new(RefCell::new(topic));
+        (CompactedTopic(topic.clone()), TopicHandle(topic))
+    }
+}
+
+impl<I, K, F> Stream for CompactedTopic<I, K, F>
+    where I: Clone,
+          K: Eq + Hash,
+          F: FnMut(&I) -> K
+{
+    type Item = I;
+    type Error = Never;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut topic = self.0.borrow_mut();
+
+        while topic.offset < topic.slots.len() {
+            let offset = topic.offset;
+            topic.offset += 1;
+            if let Some(ref item) = topic.slots[offset] {
+                return Ok(Async::Ready(Some(item.clone())));
+            }
+        }
+
+        if topic.closed {
+            Ok(Async::Ready(None))
+        } else {
+            topic.waker = Some(cx.waker());
+            Ok(Async::Pending)
+        }
+    }
+}
+
+/// The handle for appending to a `CompactedTopic`.
+///
+/// Dropping the handle ends the stream once it has read all values.
+pub struct TopicHandle<I, K, F>(Rc<RefCell<Topic<I, K, F>>>);
+
+impl<I, K, F> TopicHandle<I, K, F>
+    where K: Eq + Hash,
+          F: FnMut(&I) -> K
+{
+    /// Append an entry to the topic, compacting it and waking the stream.
+    pub fn append(&self, entry: TopicEntry<I, K>) {
+        self.0.borrow_mut().append(entry);
+    }
+}
+
+impl<I, K, F> Drop for TopicHandle<I, K, F> {
+    fn drop(&mut self) {
+        let mut topic = self.0.borrow_mut();
+        topic.closed = true;
+        if let Some(waker) = topic.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::executor::block_on;
+
+    #[test]
+    fn yields_latest_values_without_tombstoned() {
+        let (topic, handle) = CompactedTopic::new(vec![TopicEntry::Value(('a', 0)),
+                                                       TopicEntry::Value(('b', 0))],
+                                                  |&(key, _)| key);
+        handle.append(TopicEntry::Value(('c', 0)));
+        handle.append(TopicEntry::Value(('a', 1)));
+        handle.append(TopicEntry::Tombstone('b'));
+        handle.append(TopicEntry::Value(('c', 1)));
+        drop(handle);
+
+        assert_eq!(block_on(topic.collect()).unwrap(), vec![('a', 1), ('c', 1)]);
+    }
+}
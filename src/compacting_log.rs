@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// A stream adapter simulating an append-only log that compacts under backpressure.
+///
+/// Whenever it is polled, the items the inner stream has ready are pulled into a buffer. If the
+/// buffer already holds an item with the same key (as computed by `F`), the older item is dropped
+/// and the new one is appended, so a lagging consumer only sees the latest item per key.
+///
+/// A single poll pulls at most `MAX_PULLS_PER_POLL` items, and none while the buffer already holds
+/// that many.
+pub struct CompactingLog<S: Stream, K, F> {
+    inner: S,
+    key_fn: F,
+    buffer: VecDeque<(K, S::Item)>,
+    error: Option<S::Error>,
+    done: bool,
+    compacted: usize,
+}
+
+impl<S, K, F> CompactingLog<S, K, F>
+    where S: Stream,
+          K: PartialEq,
+          F: FnMut(&S::Item) -> K
+{
+    /// Create a new `CompactingLog`, using `key_fn` to determine which items supersede each other.
+    pub fn with_key(inner: S, key_fn: F) -> CompactingLog<S, K, F> {
+        CompactingLog {
+            inner,
+            key_fn,
+            buffer: VecDeque::new(),
+            error: None,
+            done: false,
+            compacted: 0,
+        }
+    }
+
+    /// Return how many items have been dropped because a newer item with the same key arrived.
+    pub fn compacted_count(&self) -> usize {
+        self.compacted
+    }
+
+    fn append(&mut self, item: S::Item) {
+        let key = (self.key_fn)(&item);
+        if let Some(index) = self.buffer.iter().position(|(k, _)| *k == key) {
+            self.buffer.remove(index);
+            self.compacted += 1;
+        }
+        self.buffer.push_back((key, item));
+    }
+}
+
+impl<S, K, F> Stream for CompactingLog<S, K, F>
+    where S: Stream,
+          K: PartialEq,
+          F: FnMut(&S::Item) -> K
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut pulls = 0;
+        while !self.done && self.error.is_none() && pulls < ::MAX_PULLS_PER_POLL &&
+              self.buffer.len() < ::MAX_PULLS_PER_POLL {
+            pulls += 1;
+            match self.inner.poll_next(cx) {
+                Ok(Async::Ready(Some(item))) => self.append(item),
+                Ok(Async::Ready(None)) => self.done = true,
+                Ok(Async::Pending) => break,
+                Err(err) => self.error = Some(err),
+            }
+        }
+
+        match self.buffer.pop_front() {
+            Some((_, item)) => Ok(Async::Ready(Some(item))),
+            None => {
+                match self.error.take() {
+                    Some(err) => Err(err),
+                    None if self.done => Ok(Async::Ready(None)),
+                    None => Ok(Async::Pending),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, StreamExt, FutureExt};
+    use futures::sink::close;
+    use futures::stream::{iter_ok, repeat};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+    use test_channel::*;
+
+    #[test]
+    fn compacts_burst_for_lagging_consumer() {
+        let (sender, receiver) = test_channel::<(u8, char), Never>(5);
+
+        let burst = vec![Ok((1, 'a')), Ok((2, 'b')), Ok((1, 'c')), Ok((3, 'd')), Ok((2, 'e'))];
+        assert!(block_on(sender
+                             .send_all(iter_ok::<_, Never>(burst))
+                             .and_then(|(sender, _)| close(sender)))
+                        .is_ok());
+
+        let mut log = CompactingLog::with_key(receiver, |&(key, _)| key);
+        let items = block_on((&mut log).collect()).unwrap();
+
+        assert_eq!(items, vec![(1, 'c'), (3, 'd'), (2, 'e')]);
+        assert_eq!(log.compacted_count(), 2);
+    }
+
+    #[test]
+    fn always_ready_source() {
+        // Every item has a distinct key, so nothing is ever compacted.
+        let mut count = 0;
+        let source = repeat::<_, Never>(()).map(move |()| {
+            count += 1;
+            count
+        });
+        let mut log = CompactingLog::with_key(source, |&i| i);
+
+        with_noop_context(|cx| {
+            for i in 1..100 {
+                assert_eq!(log.poll_next(cx), Ok(Async::Ready(Some(i))));
+                assert!(log.buffer.len() <= ::MAX_PULLS_PER_POLL);
+            }
+        });
+    }
+}
@@ -0,0 +1,245 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::{Context, Waker};
+
+use wake_all::WakeAll;
+
+struct Group<S: Stream> {
+    source: S,
+    done: bool,
+    partitions: Vec<VecDeque<S::Item>>,
+    assignment: Vec<usize>,
+    num_consumers: usize,
+    wakers: Arc<WakeAll>,
+}
+
+/// A deterministic model of a consumer group sharing a partitioned source.
+///
+/// The source yields pairs of a partition key and an item, the partition of an item being its key
+/// modulo the number of partitions. Each partition is assigned to exactly one `GroupConsumer`,
+/// which receives the items of that partition in order. Initially, partition `p` is assigned to
+/// consumer `p % num_consumers`.
+///
+/// Items pulled from the source but not yet yielded by a consumer are buffered per partition.
+/// When `rebalance` moves a partition to a different consumer, these in-flight items are
+/// delivered to the newly assigned consumer instead, so no item is lost or delivered twice. To
+/// keep these buffers bounded, a consumer pulls at most `MAX_PULLS_PER_POLL` items per poll, and
+/// none while a partition already buffers that many.
+pub struct ConsumerGroup<S: Stream>(Rc<RefCell<Group<S>>>);
+
+impl<I, S> ConsumerGroup<S>
+    where S: Stream<Item = (usize, I)>
+{
+    /// Create a new `ConsumerGroup` of `num_consumers` consumers, reading from `source` with
+    /// `num_partitions` partitions, returning the group handle and the consumers.
+    ///
+    /// # Panics
+    /// Panics if `num_consumers` or `num_partitions` is 0.
+    pub fn new(source: S,
+               num_consumers: usize,
+               num_partitions: usize)
+               -> (ConsumerGroup<S>, Vec<GroupConsumer<S>>) {
+        if num_consumers == 0 || num_partitions == 0 {
+            panic!("ConsumerGroup must have at least one consumer and at least one partition")
+        }
+
+        let group = Rc::new(RefCell::new(Group {
+                                             source,
+                                             done: false,
+                                             partitions: (0..num_partitions)
+                                                 .map(|_| VecDeque::new())
+                                                 .collect(),
+                                             assignment: (0..num_partitions)
+                                                 .map(|partition| partition % num_consumers)
+                                                 .collect(),
+                                             num_consumers,
+                                             wakers: Default::default(),
+                                         }));
+
+        let consumers = (0..num_consumers)
+            .map(|id| {
+                     GroupConsumer {
+                         id,
+                         next: 0,
+                         group: group.clone(),
+                     }
+                 })
+            .collect();
+
+        (ConsumerGroup(group), consumers)
+    }
+
+    /// Reassign the partitions, `assignment[p]` being the id of the consumer to receive partition
+    /// `p` from now on. All consumers are woken.
+    ///
+    /// # Panics
+    /// Panics if `assignment` does not contain exactly one entry per partition, or if it refers to
+    /// a nonexistent consumer.
+    pub fn rebalance(&self, assignment: Vec<usize>) {
+        let mut group = self.0.borrow_mut();
+        if assignment.len() != group.partitions.len() {
+            panic!("Assignment must assign each partition to a consumer")
+        }
+        if assignment.iter().any(|&consumer| consumer >= group.num_consumers) {
+            panic!("Assignment refers to a nonexistent consumer")
+        }
+        group.assignment = assignment;
+        Waker::from(group.wakers.clone()).wake();
+    }
+
+    /// Return the current assignment of partitions to consumers.
+    pub fn assignment(&self) -> Vec<usize> {
+        self.0.borrow().assignment.clone()
+    }
+}
+
+/// A consumer of a `ConsumerGroup`, yielding pairs of a partition and an item.
+///
+/// It takes turns among its assigned partitions that have buffered items. It ends once the source
+/// has ended and no partition has buffered items, since a `rebalance` may still assign a partition
+/// with buffered items to it.
+pub struct GroupConsumer<S: Stream> {
+    id: usize,
+    next: usize,
+    group: Rc<RefCell<Group<S>>>,
+}
+
+impl<I, S> Stream for GroupConsumer<S>
+    where S: Stream<Item = (usize, I)>
+{
+    type Item = (usize, I);
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut group = self.group.borrow_mut();
+        let group = &mut *group;
+        let num_partitions = group.partitions.len();
+
+        group.wakers.register(self.id, cx.waker());
+        let waker = Waker::from(group.wakers.clone());
+        let mut routed = false;
+
+        let mut full = group.partitions
+            .iter()
+            .any(|partition| partition.len() >= ::MAX_PULLS_PER_POLL);
+        let mut pulls = 0;
+        while !group.done && !full {
+            if pulls == ::MAX_PULLS_PER_POLL {
+                // The source may have more items ready, so all consumers need to poll again.
+                routed = true;
+                break;
+            }
+            pulls += 1;
+            match group.source.poll_next(&mut cx.with_waker(&waker))? {
+                Async::Ready(Some((key, item))) => {
+                    let partition = &mut group.partitions[key % num_partitions];
+                    partition.push_back((key, item));
+                    full = partition.len() >= ::MAX_PULLS_PER_POLL;
+                    routed = true;
+                }
+                Async::Ready(None) => {
+                    group.done = true;
+                    routed = true;
+                }
+                Async::Pending => break,
+            }
+        }
+
+        if routed {
+            waker.wake();
+        }
+
+        for i in 0..num_partitions {
+            let partition = (self.next + i) % num_partitions;
+            if group.assignment[partition] != self.id {
+                continue;
+            }
+            if let Some((_, item)) = group.partitions[partition].pop_front() {
+                if group.done && group.partitions.iter().all(|partition| partition.is_empty()) {
+                    // The other consumers can end now.
+                    waker.wake();
+                }
+                self.next = partition + 1;
+                return Ok(Async::Ready(Some((partition, item))));
+            }
+        }
+
+        if group.done && group.partitions.iter().all(|partition| partition.is_empty()) {
+            Ok(Async::Ready(None))
+        } else {
+            Ok(Async::Pending)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{StreamExt, FutureExt};
+    use futures::stream::{iter_ok, repeat};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+
+    #[test]
+    fn rebalance_loses_and_duplicates_nothing() {
+        let source = iter_ok::<_, Never>((0..8).map(|i| (i, i)));
+        let (group, mut consumers) = ConsumerGroup::new(source, 2, 2);
+        let mut consumer_b = consumers.pop().unwrap();
+        let mut consumer_a = consumers.pop().unwrap();
+
+        with_noop_context(|cx| {
+            assert_eq!(consumer_a.poll_next(cx), Ok(Async::Ready(Some((0, 0)))));
+            assert_eq!(consumer_b.poll_next(cx), Ok(Async::Ready(Some((1, 1)))));
+        });
+
+        group.rebalance(vec![1, 0]);
+        assert_eq!(group.assignment(), vec![1, 0]);
+
+        let mut items: Vec<usize> = vec![0, 1];
+        let (rest_a, rest_b) = block_on(consumer_a.collect().join(consumer_b.collect())).unwrap();
+        assert!(rest_a.iter().all(|&(partition, _)| partition == 1));
+        assert!(rest_b.iter().all(|&(partition, _)| partition == 0));
+
+        items.extend(rest_a.into_iter().chain(rest_b).map(|(_, item)| item));
+        items.sort();
+        assert_eq!(items, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn rebalance_after_source_ended() {
+        let source = iter_ok::<_, Never>((0..4).map(|i| (i, i)));
+        let (group, mut consumers) = ConsumerGroup::new(source, 2, 2);
+        let consumer_b = consumers.pop().unwrap();
+        let mut consumer_a = consumers.pop().unwrap();
+
+        with_noop_context(|cx| {
+            assert_eq!(consumer_a.poll_next(cx), Ok(Async::Ready(Some((0, 0)))));
+            assert_eq!(consumer_a.poll_next(cx), Ok(Async::Ready(Some((0, 2)))));
+            assert_eq!(consumer_a.poll_next(cx), Ok(Async::Pending));
+        });
+
+        group.rebalance(vec![0, 0]);
+        assert_eq!(block_on(consumer_a.collect()).unwrap(), vec![(1, 1), (1, 3)]);
+        assert_eq!(block_on(consumer_b.collect()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn always_ready_source() {
+        let (group, mut consumers) = ConsumerGroup::new(repeat::<_, Never>((1, 7)), 2, 2);
+
+        with_noop_context(|cx| {
+            for _ in 0..3 {
+                assert_eq!(consumers[0].poll_next(cx), Ok(Async::Pending));
+                assert_eq!(consumers[1].poll_next(cx), Ok(Async::Ready(Some((1, 7)))));
+                assert!(group.0.borrow().partitions[1].len() <= ::MAX_PULLS_PER_POLL);
+            }
+        });
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+use step::noop_waker;
+
+/// A stream wrapper fuzzing the poll contract of the inner stream with spurious polls.
+///
+/// Before forwarding a poll, the wrapper polls the inner stream a pseudo-random number of
+/// additional times (derived from `seed`) with a waker that does nothing, buffering any items
+/// yielded by these spurious polls. It asserts that the inner stream never yields an item equal to
+/// one it yielded before, and never yields an item after it ended. To check the latter, the inner
+/// stream keeps receiving spurious polls after it ended. An error emitted during a spurious poll
+/// is held back until the items buffered before it have been yielded.
+///
+/// # Panics
+/// Polling panics with a report including the seed if the inner stream violates the contract.
+pub struct ContractStream<S: Stream> {
+    inner: S,
+    seed: u64,
+    rng: u64,
+    yielded: Vec<S::Item>,
+    buffer: VecDeque<S::Item>,
+    error: Option<S::Error>,
+    done: bool,
+}
+
+impl<S> ContractStream<S>
+    where S: Stream,
+          S::Item: Clone + PartialEq + Debug
+{
+    /// Create a new `ContractStream`, deriving the spurious polls from `seed`.
+    pub fn new(inner: S, seed: u64) -> ContractStream<S> {
+        ContractStream {
+            inner,
+            seed,
+            rng: seed | 1,
+            yielded: Vec::new(),
+            buffer: VecDeque::new(),
+            error: None,
+            done: false,
+        }
+    }
+
+    // xorshift64
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    // Check an item yielded by the inner stream, then buffer it.
+    fn record(&mut self, item: S::Item) {
+        if self.done {
+            panic!("Stream yielded {:?} after it ended (seed {})", item, self.seed);
+        }
+        if self.yielded.contains(&item) {
+            panic!("Stream yielded {:?} twice (seed {}, {} items yielded before)",
+                   item,
+                   self.seed,
+                   self.yielded.len());
+        }
+        self.yielded.push(item.clone());
+        self.buffer.push_back(item);
+    }
+
+    // Poll the inner stream, recording the outcome.
+    fn poll_inner(&mut self, cx: &mut Context) -> Poll<(), S::Error> {
+        match self.inner.poll_next(cx)? {
+            Async::Ready(Some(item)) => {
+                self.record(item);
+                Ok(Async::Ready(()))
+            }
+            Async::Ready(None) => {
+                self.done = true;
+                Ok(Async::Ready(()))
+            }
+            Async::Pending => Ok(Async::Pending),
+        }
+    }
+}
+
+impl<S> Stream for ContractStream<S>
+    where S: Stream,
+          S::Item: Clone + PartialEq + Debug
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let waker = noop_waker();
+        for _ in 0..self.next_random() % 3 {
+            if self.error.is_some() {
+                break;
+            }
+            if let Err(err) = self.poll_inner(&mut cx.with_waker(&waker)) {
+                self.error = Some(err);
+            }
+        }
+
+        if self.buffer.is_empty() {
+            if let Some(err) = self.error.take() {
+                return Err(err);
+            }
+        }
+
+        if self.buffer.is_empty() && !self.done {
+            if let Async::Pending = self.poll_inner(cx)? {
+                return Ok(Async::Pending);
+            }
+        }
+
+        match self.buffer.pop_front() {
+            Some(item) => Ok(Async::Ready(Some(item))),
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::stream::{iter_ok, iter_result};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    // Yields the items of a range, returning `Pending` before each odd item, but then wrongly
+    // yields the previous item again.
+    struct Stale {
+        next: u8,
+        end: u8,
+        was_pending: bool,
+    }
+
+    impl Stream for Stale {
+        type Item = u8;
+        type Error = Never;
+
+        fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+            if self.was_pending && self.next > 0 {
+                self.was_pending = false;
+                return Ok(Async::Ready(Some(self.next - 1)));
+            }
+            if self.next == self.end {
+                return Ok(Async::Ready(None));
+            }
+            if self.next % 2 == 1 && !self.was_pending {
+                self.was_pending = true;
+                cx.waker().wake();
+                return Ok(Async::Pending);
+            }
+            self.next += 1;
+            Ok(Async::Ready(Some(self.next - 1)))
+        }
+    }
+
+    #[test]
+    fn correct_stream_passes() {
+        for seed in 0..16 {
+            let stream = ContractStream::new(iter_ok::<_, Never>(0..8), seed);
+            assert_eq!(block_on(stream.collect()).unwrap(), (0..8).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn error_follows_buffered_items() {
+        for seed in 0..16 {
+            let inner = iter_result::<_, u8, ()>(vec![Ok(0), Ok(1), Ok(2), Err(())]);
+            let results = ContractStream::new(inner, seed).then(Ok::<_, Never>).collect();
+            assert_eq!(block_on(results).unwrap(), vec![Ok(0), Ok(1), Ok(2), Err(())]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "twice")]
+    fn stale_stream_fails() {
+        let stale = Stale {
+            next: 0,
+            end: 8,
+            was_pending: false,
+        };
+        block_on(ContractStream::new(stale, 3).collect()).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+use futures_core::{Poll, Async, Never};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink that discards all items it receives, only counting them.
+///
+/// This is useful as the terminal sink of a test, when only the amount of data matters.
+pub struct CountingSink<T> {
+    count: usize,
+    bytes: usize,
+    measure: Option<fn(&T) -> usize>,
+}
+
+impl<T> CountingSink<T> {
+    /// Create a new `CountingSink` that counts the items it receives.
+    pub fn new() -> CountingSink<T> {
+        CountingSink {
+            count: 0,
+            bytes: 0,
+            measure: None,
+        }
+    }
+
+    /// Return how many items this sink has received.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl<T: AsRef<[u8]>> CountingSink<T> {
+    /// Create a new `CountingSink` that counts both the items and the bytes it receives.
+    pub fn counting_bytes() -> CountingSink<T> {
+        CountingSink {
+            count: 0,
+            bytes: 0,
+            measure: Some(|item| item.as_ref().len()),
+        }
+    }
+
+    /// Return how many bytes this sink has received in total, or `None` if the sink was created
+    /// via `new` rather than `counting_bytes` and thus does not count bytes.
+    pub fn byte_count(&self) -> Option<usize> {
+        self.measure.map(|_| self.bytes)
+    }
+}
+
+impl<T> Default for CountingSink<T> {
+    fn default() -> CountingSink<T> {
+        CountingSink::new()
+    }
+}
+
+impl<T> Sink for CountingSink<T> {
+    type SinkItem = T;
+    type SinkError = Never;
+
+    fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.count += 1;
+        if let Some(measure) = self.measure {
+            self.bytes += measure(&item);
+        }
+        Ok(())
+    }
+
+    fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+
+    fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+
+    use send_all_close::SendAllClose;
+
+    #[test]
+    fn counts_items_and_bytes() {
+        let items = vec![vec![0u8; 3], vec![], vec![1; 4]];
+        let sink = block_on(SendAllClose::new(CountingSink::counting_bytes(), items)).unwrap();
+        assert_eq!(sink.count(), 3);
+        assert_eq!(sink.byte_count(), Some(7));
+    }
+
+    #[test]
+    fn new_does_not_count_bytes() {
+        let sink = block_on(SendAllClose::new(CountingSink::new(), vec![vec![0u8; 3]])).unwrap();
+        assert_eq!(sink.count(), 1);
+        assert_eq!(sink.byte_count(), None);
+    }
+}
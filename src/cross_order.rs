@@ -0,0 +1,237 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// An item of a `CrossOrder`, tagged with `K` and routed to one of its two sinks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrossItem<K, T, U> {
+    /// An item for the first sink.
+    A(K, T),
+    /// An item for the second sink.
+    B(K, U),
+}
+
+/// The error type of a `CrossOrder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrossOrderError<EA, EB> {
+    /// The first sink emitted an error.
+    A(EA),
+    /// The second sink emitted an error.
+    B(EB),
+    /// The `CrossOrder` was closed while this many items for the second sink were still waiting
+    /// for their counterpart in the first sink.
+    Unmatched(usize),
+}
+
+/// A sink routing tagged items to two sinks, enforcing that an item with tag `t` reaches the
+/// second sink only after an item with tag `t` has been accepted by the first sink.
+///
+/// Items for the second sink whose counterpart has not been sent yet are buffered. Since it is
+/// not known in advance which sink the next item goes to, the `CrossOrder` is only ready when
+/// both sinks are.
+pub struct CrossOrder<A: Sink, B: Sink, K> {
+    a: A,
+    b: B,
+    accepted: HashSet<K>,
+    waiting: Vec<(K, B::SinkItem)>,
+    released: VecDeque<B::SinkItem>,
+}
+
+impl<A, B, K> CrossOrder<A, B, K>
+    where A: Sink,
+          B: Sink,
+          K: Eq + Hash
+{
+    /// Create a new `CrossOrder` routing into `a` and `b`.
+    pub fn new(a: A, b: B) -> CrossOrder<A, B, K> {
+        CrossOrder {
+            a,
+            b,
+            accepted: HashSet::new(),
+            waiting: Vec::new(),
+            released: VecDeque::new(),
+        }
+    }
+
+    /// Return how many items for the second sink are waiting for their counterpart.
+    pub fn waiting(&self) -> usize {
+        self.waiting.len()
+    }
+
+    /// Consume the `CrossOrder`, returning the two wrapped sinks.
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+
+    // Send all released items into the second sink.
+    fn poll_released(&mut self,
+                     cx: &mut Context)
+                     -> Poll<(), CrossOrderError<A::SinkError, B::SinkError>> {
+        while !self.released.is_empty() {
+            if let Async::Pending = self.b.poll_ready(cx).map_err(CrossOrderError::B)? {
+                return Ok(Async::Pending);
+            }
+            let item = self.released.pop_front().unwrap();
+            self.b.start_send(item).map_err(CrossOrderError::B)?;
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<A, B, K> Sink for CrossOrder<A, B, K>
+    where A: Sink,
+          B: Sink,
+          K: Eq + Hash
+{
+    type SinkItem = CrossItem<K, A::SinkItem, B::SinkItem>;
+    type SinkError = CrossOrderError<A::SinkError, B::SinkError>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.poll_released(cx)? {
+            return Ok(Async::Pending);
+        }
+        if let Async::Pending = self.a.poll_ready(cx).map_err(CrossOrderError::A)? {
+            return Ok(Async::Pending);
+        }
+        self.b.poll_ready(cx).map_err(CrossOrderError::B)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        match item {
+            CrossItem::A(tag, item) => {
+                self.a.start_send(item).map_err(CrossOrderError::A)?;
+
+                let waiting = ::std::mem::take(&mut self.waiting);
+                for (waiting_tag, waiting_item) in waiting {
+                    if waiting_tag == tag {
+                        self.released.push_back(waiting_item);
+                    } else {
+                        self.waiting.push((waiting_tag, waiting_item));
+                    }
+                }
+                self.accepted.insert(tag);
+                Ok(())
+            }
+            CrossItem::B(tag, item) => {
+                if !self.accepted.contains(&tag) {
+                    self.waiting.push((tag, item));
+                    Ok(())
+                } else if self.released.is_empty() {
+                    self.b.start_send(item).map_err(CrossOrderError::B)
+                } else {
+                    self.released.push_back(item);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.poll_released(cx)? {
+            return Ok(Async::Pending);
+        }
+        if let Async::Pending = self.a.poll_flush(cx).map_err(CrossOrderError::A)? {
+            return Ok(Async::Pending);
+        }
+        self.b.poll_flush(cx).map_err(CrossOrderError::B)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if !self.waiting.is_empty() {
+            return Err(CrossOrderError::Unmatched(self.waiting.len()));
+        }
+        if let Async::Pending = self.poll_released(cx)? {
+            return Ok(Async::Pending);
+        }
+        if let Async::Pending = self.a.poll_close(cx).map_err(CrossOrderError::A)? {
+            return Ok(Async::Pending);
+        }
+        self.b.poll_close(cx).map_err(CrossOrderError::B)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::sink::close;
+    use futures::stream::iter_ok;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    type SharedLog = Rc<RefCell<Vec<(char, u8)>>>;
+
+    // Appends all items, tagged with its name, to a log shared with other sinks.
+    struct Log {
+        name: char,
+        log: SharedLog,
+    }
+
+    impl Sink for Log {
+        type SinkItem = u8;
+        type SinkError = Never;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.log.borrow_mut().push((self.name, item));
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    fn logs() -> (Log, Log, SharedLog) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        (Log {
+             name: 'a',
+             log: log.clone(),
+         },
+         Log {
+             name: 'b',
+             log: log.clone(),
+         },
+         log)
+    }
+
+    #[test]
+    fn b_waits_for_a() {
+        let (a, b, log) = logs();
+        let items = vec![CrossItem::B(1, 10),
+                         CrossItem::B(2, 20),
+                         CrossItem::A(2, 2),
+                         CrossItem::B(2, 21),
+                         CrossItem::A(1, 1)];
+
+        let sink = CrossOrder::new(a, b);
+        block_on(sink.send_all(iter_ok(items)).and_then(|(sink, _)| close(sink))).unwrap();
+        assert_eq!(*log.borrow(),
+                   vec![('a', 2), ('b', 20), ('b', 21), ('a', 1), ('b', 10)]);
+    }
+
+    #[test]
+    fn close_with_unmatched_errors() {
+        let (a, b, _) = logs();
+        let sink = CrossOrder::new(a, b);
+
+        let sink = block_on(sink.send(CrossItem::B(0, 0))).unwrap();
+        assert_eq!(sink.waiting(), 1);
+        assert_eq!(block_on(close(sink)).err(),
+                   Some(CrossOrderError::Unmatched(1)));
+    }
+}
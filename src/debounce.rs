@@ -0,0 +1,168 @@
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+use clock::LogicalClock;
+
+/// A stream combinator that debounces rapid items, measuring time in polls.
+///
+/// Every item replaces the previously held one. The held item is only yielded once the inner
+/// stream returned `Pending` for `n` consecutive polls without producing a new item. While waiting
+/// for the quiet period to elapse, the task is woken immediately so that it polls again. When the
+/// inner stream ends, a held item is yielded right away.
+///
+/// Alternatively, the quiet period can be measured on a `LogicalClock`, see `with_clock`.
+pub struct Debounce<S: Stream> {
+    inner: S,
+    n: u64,
+    latest: Option<S::Item>,
+    // The number of quiet polls so far, or the time of the latest item when using a clock.
+    quiet: u64,
+    clock: Option<LogicalClock>,
+    done: bool,
+}
+
+impl<S: Stream> Debounce<S> {
+    /// Create a new `Debounce`, requiring a quiet period of `n` polls before yielding an item.
+    pub fn new(inner: S, n: u64) -> Debounce<S> {
+        Debounce {
+            inner,
+            n,
+            latest: None,
+            quiet: 0,
+            clock: None,
+            done: false,
+        }
+    }
+
+    /// Create a new `Debounce`, requiring that the given clock advanced by at least `n` since the
+    /// latest item arrived before yielding it.
+    ///
+    /// Instead of waking itself immediately, the task is woken when the clock is advanced.
+    pub fn with_clock(inner: S, n: u64, clock: LogicalClock) -> Debounce<S> {
+        Debounce {
+            clock: Some(clock),
+            ..Debounce::new(inner, n)
+        }
+    }
+
+    /// Consume the `Debounce`, returning the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Stream> Stream for Debounce<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        while !self.done {
+            match self.inner.poll_next(cx)? {
+                Async::Ready(Some(item)) => {
+                    self.latest = Some(item);
+                    self.quiet = match self.clock {
+                        Some(ref clock) => clock.now(),
+                        None => 0,
+                    };
+                }
+                Async::Ready(None) => self.done = true,
+                Async::Pending => {
+                    if self.latest.is_none() {
+                        return Ok(Async::Pending);
+                    }
+
+                    match self.clock {
+                        Some(ref clock) => {
+                            if clock.now() - self.quiet >= self.n {
+                                return Ok(Async::Ready(self.latest.take()));
+                            }
+                            clock.wake_on_advance(cx);
+                        }
+                        None => {
+                            self.quiet += 1;
+                            if self.quiet >= self.n {
+                                self.quiet = 0;
+                                return Ok(Async::Ready(self.latest.take()));
+                            }
+                            cx.waker().wake();
+                        }
+                    }
+
+                    return Ok(Async::Pending);
+                }
+            }
+        }
+
+        Ok(Async::Ready(self.latest.take()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::VecDeque;
+
+    use futures::{SinkExt, StreamExt, FutureExt};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+    use test_channel::*;
+
+    // Yields the scripted items, where `None` means returning `Pending` once (waking immediately).
+    struct Script(VecDeque<Option<u8>>);
+
+    impl Stream for Script {
+        type Item = u8;
+        type Error = Never;
+
+        fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+            match self.0.pop_front() {
+                Some(Some(item)) => Ok(Async::Ready(Some(item))),
+                Some(None) => {
+                    cx.waker().wake();
+                    Ok(Async::Pending)
+                }
+                None => Ok(Async::Ready(None)),
+            }
+        }
+    }
+
+    #[test]
+    fn burst_then_quiet() {
+        let script = vec![Some(0),
+                          Some(1),
+                          None,
+                          Some(2),
+                          None,
+                          None,
+                          None,
+                          Some(3),
+                          Some(4),
+                          None,
+                          None];
+        let debounced = Debounce::new(Script(script.into_iter().collect()), 3);
+
+        assert_eq!(block_on(debounced.collect()).unwrap(), vec![2, 4]);
+    }
+
+    #[test]
+    fn clock_advance_ends_quiet_period() {
+        let (sender, receiver) = test_channel::<u8, Never>(4);
+        let clock = LogicalClock::new();
+        let mut debounced = Debounce::with_clock(receiver, 5, clock.clone());
+
+        let sender = block_on(sender.send(Ok(0)).and_then(|sender| sender.send(Ok(1)))).unwrap();
+        with_noop_context(|cx| {
+            assert_eq!(debounced.poll_next(cx), Ok(Async::Pending));
+            clock.advance(4);
+            assert_eq!(debounced.poll_next(cx), Ok(Async::Pending));
+            clock.advance(1);
+            assert_eq!(debounced.poll_next(cx), Ok(Async::Ready(Some(1))));
+        });
+
+        drop(sender);
+        assert_eq!(block_on(debounced.collect()).unwrap(), vec![]);
+    }
+}
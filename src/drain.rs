@@ -0,0 +1,66 @@
+use futures_core::{Future, Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// Future which reads a stream to completion, discarding all items, and then yields the stream.
+///
+/// This is like collecting the stream, but without allocating for items nobody looks at.
+pub struct Drain<S> {
+    stream: Option<S>,
+}
+
+impl<S: Stream> Drain<S> {
+    /// Create a new `Drain` future that drains the given stream.
+    pub fn new(stream: S) -> Drain<S> {
+        Drain { stream: Some(stream) }
+    }
+}
+
+impl<S: Stream> Future for Drain<S> {
+    type Item = S;
+    type Error = S::Error;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::Error> {
+        {
+            let stream = self.stream
+                .as_mut()
+                .expect("Attempted to poll Drain after completion");
+
+            loop {
+                match stream.poll_next(cx)? {
+                    Async::Ready(Some(_)) => {}
+                    Async::Ready(None) => break,
+                    Async::Pending => return Ok(Async::Pending),
+                }
+            }
+        }
+
+        Ok(Async::Ready(self.stream.take().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::FutureExt;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use send_all_close::SendAllClose;
+    use test_channel::test_channel;
+
+    #[test]
+    fn drains_receiver() {
+        let (sender, receiver) = test_channel::<u8, Never>(1);
+        let send = SendAllClose::new(sender, (0..8).map(Ok));
+        block_on(send.join(Drain::new(receiver))).unwrap();
+    }
+
+    #[test]
+    fn errors_short_circuit() {
+        let (mut sender, receiver) = test_channel(2);
+        sender.try_send(Ok(0)).unwrap();
+        sender.try_send(Err("failed")).unwrap();
+        assert_eq!(block_on(Drain::new(receiver)).err(), Some("failed"));
+    }
+}
@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures_core::{Stream, Poll, Async, Never};
+use futures_core::task::{Context, Waker};
+
+struct Log<I> {
+    events: Vec<I>,
+    offset: usize,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<I> Log<I> {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A stream replaying a durable event log, which can be rewound through a `LogHandle`.
+///
+/// The stream yields (clones of) the events from the current offset onwards. Once the offset
+/// reaches the end of the log, the stream returns `Pending` until new events are appended. It ends
+/// when the handle has been dropped and all events up to the end of the log were yielded.
+pub struct EventLog<I>(Rc<RefCell<Log<I>>>);
+
+impl<I: Clone> EventLog<I> {
+    /// Create a new `EventLog` containing the given events, together with its handle.
+    pub fn new(initial_events: Vec<I>) -> (EventLog<I>, LogHandle<I>) {
+        let log = Rc::new(RefCell::new(Log {
+                                           events: initial_events,
+                                           offset: 0,
+                                           closed: false,
+                                           waker: None,
+                                       }));
+        (EventLog(log.clone()), LogHandle(log))
+    }
+}
+
+impl<I: Clone> Stream for EventLog<I> {
+    type Item = I;
+    type Error = Never;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut log = self.0.borrow_mut();
+
+        if log.offset < log.events.len() {
+            let event = log.events[log.offset].clone();
+            log.offset += 1;
+            Ok(Async::Ready(Some(event)))
+        } else if log.closed {
+            Ok(Async::Ready(None))
+        } else {
+            log.waker = Some(cx.waker());
+            Ok(Async::Pending)
+        }
+    }
+}
+
+/// The handle for appending to and seeking within an `EventLog`.
+///
+/// Dropping the handle ends the stream once it reaches the end of the log.
+pub struct LogHandle<I>(Rc<RefCell<Log<I>>>);
+
+impl<I> LogHandle<I> {
+    /// Append an event to the end of the log, waking the stream.
+    pub fn append(&self, event: I) {
+        let mut log = self.0.borrow_mut();
+        log.events.push(event);
+        log.wake();
+    }
+
+    /// Set the offset of the next event the stream yields, waking the stream.
+    ///
+    /// The offset may lie past the end of the log, in which case the stream stays pending until
+    /// enough events have been appended.
+    pub fn seek(&self, offset: usize) {
+        let mut log = self.0.borrow_mut();
+        log.offset = offset;
+        log.wake();
+    }
+
+    /// Return the offset of the next event the stream yields.
+    pub fn offset(&self) -> usize {
+        self.0.borrow().offset
+    }
+}
+
+impl<I> Drop for LogHandle<I> {
+    fn drop(&mut self) {
+        let mut log = self.0.borrow_mut();
+        log.closed = true;
+        log.wake();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::executor::block_on;
+
+    use step::with_noop_context;
+
+    #[test]
+    fn replays_after_seek() {
+        let (mut log, handle) = EventLog::new(vec![0, 1, 2]);
+
+        with_noop_context(|cx| {
+            for i in 0..3 {
+                assert_eq!(log.poll_next(cx), Ok(Async::Ready(Some(i))));
+            }
+            assert_eq!(log.poll_next(cx), Ok(Async::Pending));
+
+            handle.seek(5);
+            handle.append(3);
+            assert_eq!(log.poll_next(cx), Ok(Async::Pending));
+        });
+
+        handle.seek(1);
+        drop(handle);
+        assert_eq!(block_on(log.collect()).unwrap(), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,153 @@
+use futures_core::{Future, Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// Future which flushes a sink and then yields it back, so that it can be used further.
+pub struct Flush<S> {
+    sink: Option<S>,
+}
+
+impl<S: Sink> Flush<S> {
+    /// Create a new `Flush` future that flushes the given sink.
+    pub fn new(sink: S) -> Flush<S> {
+        Flush { sink: Some(sink) }
+    }
+
+    /// Get a shared reference to the sink, or `None` if the future has already completed.
+    pub fn get_ref(&self) -> Option<&S> {
+        self.sink.as_ref()
+    }
+
+    /// Get a mutable reference to the sink, or `None` if the future has already completed.
+    pub fn get_mut(&mut self) -> Option<&mut S> {
+        self.sink.as_mut()
+    }
+}
+
+impl<S: Sink> Future for Flush<S> {
+    type Item = S;
+    type Error = S::SinkError;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
+        let mut sink = self.sink
+            .take()
+            .expect("Attempted to poll Flush after completion");
+
+        if let Async::Pending = sink.poll_flush(cx)? {
+            self.sink = Some(sink);
+            return Ok(Async::Pending);
+        }
+        Ok(Async::Ready(sink))
+    }
+}
+
+/// Future which first flushes a sink and then closes it, yielding it back afterwards.
+///
+/// This is useful for sinks that do not flush as part of being closed.
+pub struct FlushClose<S> {
+    sink: Option<S>,
+    flushed: bool,
+}
+
+impl<S: Sink> FlushClose<S> {
+    /// Create a new `FlushClose` future that flushes and then closes the given sink.
+    pub fn new(sink: S) -> FlushClose<S> {
+        FlushClose {
+            sink: Some(sink),
+            flushed: false,
+        }
+    }
+}
+
+impl<S: Sink> Future for FlushClose<S> {
+    type Item = S;
+    type Error = S::SinkError;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
+        {
+            let sink = self.sink
+                .as_mut()
+                .expect("Attempted to poll FlushClose after completion");
+
+            if !self.flushed {
+                if let Async::Pending = sink.poll_flush(cx)? {
+                    return Ok(Async::Pending);
+                }
+                self.flushed = true;
+            }
+
+            if let Async::Pending = sink.poll_close(cx)? {
+                return Ok(Async::Pending);
+            }
+        }
+
+        Ok(Async::Ready(self.sink.take().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+
+    use step::poll_once;
+
+    // Becomes flushed after being polled `pending` times.
+    struct SlowFlush {
+        pending: usize,
+        closed: bool,
+    }
+
+    impl Sink for SlowFlush {
+        type SinkItem = ();
+        type SinkError = ();
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, _: Self::SinkItem) -> Result<(), Self::SinkError> {
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.pending == 0 {
+                return Ok(Async::Ready(()));
+            }
+            self.pending -= 1;
+            cx.waker().wake();
+            Ok(Async::Pending)
+        }
+
+        // Refuses to close while unflushed, and does not flush by itself.
+        fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            assert_eq!(self.pending, 0);
+            self.closed = true;
+            Ok(Async::Ready(()))
+        }
+    }
+
+    #[test]
+    fn yields_flushed_sink() {
+        let mut flush = Flush::new(SlowFlush {
+                                     pending: 2,
+                                     closed: false,
+                                 });
+        assert_eq!(poll_once(&mut flush).map(|poll| poll.is_pending()), Ok(true));
+        assert_eq!(flush.get_ref().unwrap().pending, 1);
+
+        let sink = block_on(flush).unwrap();
+        assert_eq!(sink.pending, 0);
+    }
+
+    #[test]
+    fn flushes_before_closing() {
+        let sink = block_on(FlushClose::new(SlowFlush {
+                                                pending: 2,
+                                                closed: false,
+                                            }))
+                .unwrap();
+        assert!(sink.closed);
+    }
+}
@@ -0,0 +1,111 @@
+use futures_core::{Future, Stream, Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// The error type of a `ForwardClose` future.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForwardCloseError<StE, SkE> {
+    /// The stream emitted an error.
+    Stream(StE),
+    /// The sink emitted an error.
+    Sink(SkE),
+}
+
+/// Future which forwards all items of a stream into a sink and then closes the sink, for example to
+/// pipe a `TestReceiver` into a `TestSender`.
+///
+/// It completes once the stream has ended and the sink has been closed, yielding both of them.
+pub struct ForwardClose<St, Sk: Sink> {
+    halves: Option<(St, Sk)>,
+    buffered: Option<Sk::SinkItem>,
+    stream_done: bool,
+}
+
+impl<St, Sk> ForwardClose<St, Sk>
+    where St: Stream,
+          Sk: Sink<SinkItem = St::Item>
+{
+    /// Create a new `ForwardClose` future forwarding `stream` into `sink`.
+    pub fn new(stream: St, sink: Sk) -> ForwardClose<St, Sk> {
+        ForwardClose {
+            halves: Some((stream, sink)),
+            buffered: None,
+            stream_done: false,
+        }
+    }
+}
+
+impl<St, Sk> Future for ForwardClose<St, Sk>
+    where St: Stream,
+          Sk: Sink<SinkItem = St::Item>
+{
+    type Item = (St, Sk);
+    type Error = ForwardCloseError<St::Error, Sk::SinkError>;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<Self::Item, Self::Error> {
+        {
+            let (ref mut stream, ref mut sink) = *self.halves
+                .as_mut()
+                .expect("Attempted to poll ForwardClose after completion");
+
+            while !self.stream_done {
+                if let Some(item) = self.buffered.take() {
+                    if let Async::Pending = sink.poll_ready(cx).map_err(ForwardCloseError::Sink)? {
+                        self.buffered = Some(item);
+                        return Ok(Async::Pending);
+                    }
+                    sink.start_send(item).map_err(ForwardCloseError::Sink)?;
+                }
+
+                match stream.poll_next(cx).map_err(ForwardCloseError::Stream)? {
+                    Async::Ready(Some(item)) => self.buffered = Some(item),
+                    Async::Ready(None) => self.stream_done = true,
+                    Async::Pending => {
+                        sink.poll_flush(cx).map_err(ForwardCloseError::Sink)?;
+                        return Ok(Async::Pending);
+                    }
+                }
+            }
+
+            if let Async::Pending = sink.poll_close(cx).map_err(ForwardCloseError::Sink)? {
+                return Ok(Async::Pending);
+            }
+        }
+
+        Ok(Async::Ready(self.halves.take().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{StreamExt, FutureExt};
+    use futures::stream::iter_result;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use send_all_close::SendAllClose;
+    use test_channel::test_channel;
+
+    #[test]
+    fn pipes_receiver_into_sender() {
+        let (sender_a, receiver_a) = test_channel::<u8, Never>(1);
+        let (sender_b, receiver_b) = test_channel::<u8, Never>(1);
+
+        let send = SendAllClose::new(sender_a, (0..4).map(Ok));
+        let forward = ForwardClose::new(receiver_a.map(Ok), sender_b)
+            .map_err(|_| unreachable!());
+        let (_, _, items) = block_on(send.join3(forward, receiver_b.collect())).unwrap();
+        assert_eq!(items, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn stream_error_short_circuits() {
+        let stream = iter_result(vec![Ok(0), Err("failed"), Ok(1)]);
+        match block_on(ForwardClose::new(stream, Vec::new())) {
+            Err(ForwardCloseError::Stream("failed")) => {}
+            _ => panic!("ForwardClose did not fail with the stream error"),
+        }
+    }
+}
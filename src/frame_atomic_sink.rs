@@ -0,0 +1,160 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures_core::Poll;
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A part of a frame that is written in several pieces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FramePart<T> {
+    /// The first part of a frame.
+    Begin(T),
+    /// A part in the middle of a frame.
+    Middle(T),
+    /// The last part of a frame.
+    End(T),
+}
+
+/// The error type of a `FrameAtomicSink`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError<E> {
+    /// A part was sent through one handle while a frame begun through another handle was open.
+    Interleaved,
+    /// A `Middle` or `End` part was sent while no frame was open, or a `Begin` part was sent while
+    /// a frame was already open.
+    Malformed,
+    /// The inner sink emitted an error.
+    Inner(E),
+}
+
+struct Shared<S> {
+    inner: S,
+    open_frame: Option<usize>,
+    next_id: usize,
+}
+
+/// A sink wrapper asserting that frames are written whole, with no interleaving of their parts.
+///
+/// Cloning a `FrameAtomicSink` yields another handle to the same inner sink, modelling
+/// concurrent writers. Once a handle sent a `Begin` part, only that handle may send parts until it
+/// sends the matching `End` part. Everything else is rejected with a `FrameError`.
+pub struct FrameAtomicSink<S> {
+    shared: Rc<RefCell<Shared<S>>>,
+    id: usize,
+}
+
+impl<S> FrameAtomicSink<S> {
+    /// Create a new `FrameAtomicSink` wrapping the given sink.
+    pub fn new(inner: S) -> FrameAtomicSink<S> {
+        FrameAtomicSink {
+            shared: Rc::new(RefCell::new(Shared {
+                                             inner,
+                                             open_frame: None,
+                                             next_id: 1,
+                                         })),
+            id: 0,
+        }
+    }
+
+    /// Consume this handle, returning the inner sink if no other handles to it exist.
+    pub fn into_inner(self) -> Option<S> {
+        Rc::try_unwrap(self.shared)
+            .ok()
+            .map(|shared| shared.into_inner().inner)
+    }
+}
+
+impl<S> Clone for FrameAtomicSink<S> {
+    fn clone(&self) -> FrameAtomicSink<S> {
+        let id = {
+            let mut shared = self.shared.borrow_mut();
+            shared.next_id += 1;
+            shared.next_id - 1
+        };
+        FrameAtomicSink {
+            shared: self.shared.clone(),
+            id,
+        }
+    }
+}
+
+impl<T, S> Sink for FrameAtomicSink<S>
+    where S: Sink<SinkItem = FramePart<T>>
+{
+    type SinkItem = FramePart<T>;
+    type SinkError = FrameError<S::SinkError>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.shared
+            .borrow_mut()
+            .inner
+            .poll_ready(cx)
+            .map_err(FrameError::Inner)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        let mut shared = self.shared.borrow_mut();
+
+        shared.open_frame = match (shared.open_frame, &item) {
+            (Some(id), _) if id != self.id => return Err(FrameError::Interleaved),
+            (Some(_), &FramePart::Begin(_)) |
+            (None, &FramePart::Middle(_)) |
+            (None, &FramePart::End(_)) => return Err(FrameError::Malformed),
+            (_, &FramePart::End(_)) => None,
+            _ => Some(self.id),
+        };
+        shared.inner.start_send(item).map_err(FrameError::Inner)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.shared
+            .borrow_mut()
+            .inner
+            .poll_flush(cx)
+            .map_err(FrameError::Inner)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.shared
+            .borrow_mut()
+            .inner
+            .poll_close(cx)
+            .map_err(FrameError::Inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::stream::iter_ok;
+    use futures::sink::close;
+    use futures::executor::block_on;
+
+    use self::FramePart::*;
+
+    #[test]
+    fn single_frames_pass() {
+        let sink = FrameAtomicSink::new(Vec::new());
+        let parts = vec![Begin(0), Middle(1), End(2), Begin(3), End(4)];
+
+        let sink = block_on(sink.send_all(iter_ok(parts.clone()))
+                                .and_then(|(sink, _)| close(sink)))
+                .unwrap();
+        assert_eq!(sink.into_inner(), Some(parts));
+    }
+
+    #[test]
+    fn interleaving_errors() {
+        let mut a = FrameAtomicSink::new(Vec::new());
+        let mut b = a.clone();
+
+        assert_eq!(a.start_send(Begin(0)), Ok(()));
+        assert_eq!(b.start_send(Begin(10)), Err(FrameError::Interleaved));
+        assert_eq!(a.start_send(End(1)), Ok(()));
+        assert_eq!(b.start_send(Begin(10)), Ok(()));
+        assert_eq!(a.start_send(Middle(2)), Err(FrameError::Interleaved));
+    }
+}
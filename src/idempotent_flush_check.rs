@@ -0,0 +1,154 @@
+use std::fmt::Debug;
+
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink wrapper asserting that the inner sink's `poll_flush` is idempotent.
+///
+/// Whenever the inner sink finishes a flush, `poll_flush` is called a second time, which must
+/// immediately return `Ready` again. If an observer is given, the observation it takes of the
+/// inner sink must not change due to the redundant flush either.
+///
+/// # Panics
+/// Any violation of these properties causes a panic.
+pub struct IdempotentFlushCheck<S, F = fn(&S)> {
+    inner: S,
+    observer: F,
+}
+
+impl<S: Sink> IdempotentFlushCheck<S, fn(&S)> {
+    /// Create a new `IdempotentFlushCheck`, only checking that the redundant flush is `Ready`.
+    pub fn new(inner: S) -> IdempotentFlushCheck<S, fn(&S)> {
+        IdempotentFlushCheck::with_observer(inner, |_| ())
+    }
+}
+
+impl<S, O, F> IdempotentFlushCheck<S, F>
+    where S: Sink,
+          O: PartialEq + Debug,
+          F: FnMut(&S) -> O
+{
+    /// Create a new `IdempotentFlushCheck`, additionally checking that the observation taken by
+    /// `observer` (e.g. the number of items the inner sink forwarded) is not changed by the
+    /// redundant flush.
+    pub fn with_observer(inner: S, observer: F) -> IdempotentFlushCheck<S, F> {
+        IdempotentFlushCheck { inner, observer }
+    }
+
+    /// Consume the `IdempotentFlushCheck`, returning the wrapped sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, O, F> Sink for IdempotentFlushCheck<S, F>
+    where S: Sink,
+          S::SinkError: Debug,
+          O: PartialEq + Debug,
+          F: FnMut(&S) -> O
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.inner.start_send(item)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.inner.poll_flush(cx)? {
+            return Ok(Async::Pending);
+        }
+
+        let before = (self.observer)(&self.inner);
+        match self.inner.poll_flush(cx) {
+            Ok(Async::Ready(())) => {}
+            Ok(Async::Pending) => panic!("Redundant poll_flush returned Pending"),
+            Err(err) => panic!("Redundant poll_flush returned an error: {:?}", err),
+        }
+        let after = (self.observer)(&self.inner);
+        if before != after {
+            panic!("Redundant poll_flush changed the observed sink state from {:?} to {:?}",
+                   before,
+                   after);
+        }
+
+        Ok(Async::Ready(()))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    // Forwards buffered items into `forwarded` on flush, only clearing the buffer if `correct`.
+    struct Forwarder {
+        buffer: Vec<u8>,
+        forwarded: Vec<u8>,
+        correct: bool,
+    }
+
+    impl Forwarder {
+        fn new(correct: bool) -> Forwarder {
+            Forwarder {
+                buffer: Vec::new(),
+                forwarded: Vec::new(),
+                correct,
+            }
+        }
+    }
+
+    impl Sink for Forwarder {
+        type SinkItem = u8;
+        type SinkError = Never;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.buffer.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            self.forwarded.extend_from_slice(&self.buffer);
+            if self.correct {
+                self.buffer.clear();
+            }
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.poll_flush(cx)
+        }
+    }
+
+    #[test]
+    fn correct_flush_passes() {
+        let sink = IdempotentFlushCheck::with_observer(Forwarder::new(true),
+                                                       |sink| sink.forwarded.len());
+        let sink = block_on(sink.send(0).and_then(|sink| sink.send(1))).unwrap();
+        assert_eq!(sink.into_inner().forwarded, vec![0, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Redundant poll_flush changed the observed sink state")]
+    fn redraining_flush_fails() {
+        let sink = IdempotentFlushCheck::with_observer(Forwarder::new(false),
+                                                       |sink| sink.forwarded.len());
+        let _ = block_on(sink.send(0));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// A stream adapter simulating an idempotency cache with a time-to-live.
+///
+/// The key of each item is computed by `F`. An item whose key was delivered less than `ttl` polls
+/// ago is suppressed, any other item is delivered and its key is remembered. Time is measured in
+/// calls to `poll_next`, starting from the poll that delivered the key. Suppressed repeats do not
+/// extend the lifetime of a key. Expired keys are evicted from the cache.
+///
+/// After suppressing `MAX_PULLS_PER_POLL` items within a single poll, the stream wakes itself and
+/// returns `Pending`, so that the suppressed keys get a chance to expire.
+pub struct IdempotentWithTtl<S, K, F> {
+    inner: S,
+    key_fn: F,
+    ttl: u64,
+    polls: u64,
+    cache: HashMap<K, u64>,
+}
+
+impl<S, K, F> IdempotentWithTtl<S, K, F>
+    where S: Stream,
+          K: Eq + Hash,
+          F: FnMut(&S::Item) -> K
+{
+    /// Create a new `IdempotentWithTtl`, using `key_fn` to compute the idempotency key of each
+    /// item and remembering keys for `ttl` polls.
+    pub fn with_key(inner: S, key_fn: F, ttl: u64) -> IdempotentWithTtl<S, K, F> {
+        IdempotentWithTtl {
+            inner,
+            key_fn,
+            ttl,
+            polls: 0,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Return the number of keys that are currently remembered.
+    pub fn cache_size(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+impl<S, K, F> Stream for IdempotentWithTtl<S, K, F>
+    where S: Stream,
+          K: Eq + Hash,
+          F: FnMut(&S::Item) -> K
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        self.polls += 1;
+        let now = self.polls;
+        let ttl = self.ttl;
+        self.cache.retain(|_, delivered| now - *delivered < ttl);
+
+        for _ in 0..::MAX_PULLS_PER_POLL {
+            match self.inner.poll_next(cx)? {
+                Async::Ready(Some(item)) => {
+                    let key = (self.key_fn)(&item);
+                    if let Entry::Vacant(entry) = self.cache.entry(key) {
+                        entry.insert(now);
+                        return Ok(Async::Ready(Some(item)));
+                    }
+                }
+                poll => return Ok(poll),
+            }
+        }
+
+        cx.waker().wake();
+        Ok(Async::Pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_sink::Sink;
+    use futures::stream::repeat;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+    use test_channel::test_channel;
+
+    #[test]
+    fn repeat_within_ttl_is_suppressed() {
+        let (mut sender, receiver) = test_channel::<u8, Never>(4);
+        let mut stream = IdempotentWithTtl::with_key(receiver, |&item| item, 3);
+
+        with_noop_context(|cx| {
+            assert!(sender.poll_ready(cx).unwrap().is_ready());
+            sender.start_send(Ok(0)).unwrap();
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(0))));
+            assert_eq!(stream.cache_size(), 1);
+            assert_eq!(stream.poll_next(cx), Ok(Async::Pending));
+
+            // Two polls after delivery, the key is still cached.
+            assert!(sender.poll_ready(cx).unwrap().is_ready());
+            sender.start_send(Ok(0)).unwrap();
+            assert_eq!(stream.poll_next(cx), Ok(Async::Pending));
+
+            // Three polls after delivery, the key has expired.
+            assert!(sender.poll_ready(cx).unwrap().is_ready());
+            sender.start_send(Ok(0)).unwrap();
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(0))));
+            assert_eq!(stream.cache_size(), 1);
+        });
+    }
+
+    #[test]
+    fn always_repeating_source() {
+        let mut stream = IdempotentWithTtl::with_key(repeat::<_, Never>(7), |&item| item, 2);
+
+        with_noop_context(|cx| {
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(7))));
+            assert_eq!(stream.poll_next(cx), Ok(Async::Pending));
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(7))));
+        });
+    }
+}
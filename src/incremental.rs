@@ -0,0 +1,108 @@
+use std::fmt::Debug;
+
+use futures_core::{Stream, Async, Never};
+use futures_sink::Sink;
+
+use step::with_noop_context;
+use test_channel::{test_channel, TestReceiver};
+
+/// Assert that a streaming implementation agrees with a batch reference on every prefix of the
+/// input.
+///
+/// `stream_impl` is given a `TestReceiver` and must return the streaming implementation reading
+/// from it. The items of `input` are sent one at a time, and after each item the streaming
+/// implementation must yield an output that equals `batch_fn` applied to all items sent so far.
+///
+/// # Panics
+/// Panics on the first prefix for which the outputs differ, or if the streaming implementation
+/// errors, ends early, or does not yield an output for some prefix.
+pub fn assert_incremental_matches_batch<T, S, B, F, I>(stream_impl: B, mut batch_fn: F, input: I)
+    where T: Clone + Debug,
+          S: Stream,
+          S::Item: PartialEq + Debug,
+          S::Error: Debug,
+          B: FnOnce(TestReceiver<T, Never>) -> S,
+          F: FnMut(&[T]) -> S::Item,
+          I: IntoIterator<Item = T>
+{
+    let (mut sender, receiver) = test_channel(1);
+    let mut stream = stream_impl(receiver);
+    let mut prefix = Vec::new();
+
+    for item in input {
+        prefix.push(item.clone());
+
+        let output = with_noop_context(|cx| {
+            match sender.poll_ready(cx) {
+                Ok(Async::Ready(())) => {}
+                _ => panic!("Streaming implementation did not consume its input"),
+            }
+            let _ = sender.start_send(Ok(item));
+
+            match stream.poll_next(cx) {
+                Ok(Async::Ready(Some(output))) => output,
+                Ok(Async::Ready(None)) => {
+                    panic!("Streaming implementation ended early for prefix {:?}", prefix)
+                }
+                Ok(Async::Pending) => {
+                    panic!("Streaming implementation yielded no output for prefix {:?}",
+                           prefix)
+                }
+                Err(err) => {
+                    panic!("Streaming implementation emitted an error for prefix {:?}: {:?}",
+                           prefix,
+                           err)
+                }
+            }
+        });
+
+        let expected = batch_fn(&prefix);
+        if output != expected {
+            panic!("Streaming output {:?} differs from batch output {:?} for prefix {:?}",
+                   output,
+                   expected,
+                   prefix);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+
+    fn batch_sum(prefix: &[u32]) -> u32 {
+        prefix.iter().sum()
+    }
+
+    #[test]
+    fn running_sum_matches() {
+        assert_incremental_matches_batch(|receiver| {
+                                             let mut sum = 0;
+                                             receiver.map(move |item| {
+                                                              sum += item;
+                                                              sum
+                                                          })
+                                         },
+                                         batch_sum,
+                                         vec![1, 2, 0, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "differs from batch output")]
+    fn buggy_running_sum_diverges() {
+        assert_incremental_matches_batch(|receiver| {
+                                             let mut sum = 0;
+                                             receiver.map(move |item| {
+                                                              if item == 0 {
+                                                                  sum = 0;
+                                                              }
+                                                              sum += item;
+                                                              sum
+                                                          })
+                                         },
+                                         batch_sum,
+                                         vec![1, 2, 0, 4, 5]);
+    }
+}
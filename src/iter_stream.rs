@@ -0,0 +1,51 @@
+use std::vec;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// A stream that yields the items and errors of a `Vec` in order, ending after the last one.
+///
+/// Unlike `iter_ok`, the position of each error is explicit in the data. The stream is fused: Once
+/// it ended, it keeps returning `None`.
+pub struct IterStream<T, E> {
+    results: vec::IntoIter<Result<T, E>>,
+}
+
+impl<T, E> IterStream<T, E> {
+    /// Create a new `IterStream` yielding the given `results`.
+    pub fn new(results: Vec<Result<T, E>>) -> IterStream<T, E> {
+        IterStream { results: results.into_iter() }
+    }
+}
+
+impl<T, E> Stream for IterStream<T, E> {
+    type Item = T;
+    type Error = E;
+
+    fn poll_next(&mut self, _: &mut Context) -> Poll<Option<T>, E> {
+        match self.results.next() {
+            Some(Ok(item)) => Ok(Async::Ready(Some(item))),
+            Some(Err(err)) => Err(err),
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use step::with_noop_context;
+
+    #[test]
+    fn yields_in_order_and_fuses() {
+        let mut stream = IterStream::new(vec![Ok(0), Err("failed"), Ok(1)]);
+        with_noop_context(|cx| {
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(0))));
+            assert_eq!(stream.poll_next(cx), Err("failed"));
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(1))));
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(None)));
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(None)));
+        });
+    }
+}
@@ -1,4 +1,8 @@
 //! Utilities for working with `futures::Sink` and `futures::Stream`.
+//!
+//! This crate targets the `0.2` releases of futures (`futures-core`, `futures-sink` and friends).
+//! The `Future`, `Stream`, `Sink` and `Context` types it is built against are re-exported at the
+//! crate root, so that code using this crate can import the matching versions from here.
 #![deny(missing_docs)]
 
 extern crate futures_core;
@@ -8,7 +12,113 @@ extern crate futures_util;
 #[cfg(test)]
 extern crate futures;
 
+pub use futures_core::{Future, Stream};
+pub use futures_core::task::Context;
+pub use futures_sink::Sink;
+
+/// The maximum number of items the stream adapters of this crate pull from their source within a
+/// single poll.
+///
+/// Without such a limit, an always-ready source (e.g. `futures::stream::repeat`) could keep an
+/// adapter looping forever, so that it never returns from `poll_next`.
+pub const MAX_PULLS_PER_POLL: usize = 64;
+
 pub mod test_channel;
+pub mod step;
 mod send_close;
+mod send_all_close;
+mod send_many;
+mod flush;
+mod collect_close;
+mod forward_close;
+mod sink_ext;
+mod stream_ext;
+mod drain;
+mod compacting_log;
+mod buffer_probe;
+mod priority_queue_stream;
+mod idempotent_flush_check;
+mod multiplexed_connection;
+mod frame_atomic_sink;
+mod debounce;
+mod collect_errors;
+mod event_log;
+mod chain_probe;
+mod partitioned;
+mod tumbling_window;
+mod clock;
+mod wal_sink;
+mod incremental;
+mod reopen_on_error;
+mod consumer_group;
+mod order_check;
+mod idempotent_with_ttl;
+mod livelock;
+mod schema_evolve;
+mod cross_order;
+mod compacted_topic;
+mod recovery_probe;
+mod partition;
+mod churn;
+mod at_most_once;
+mod contract_stream;
+mod counting_sink;
+mod vec_sink;
+mod iter_stream;
+mod tee;
+mod strict_close;
+mod close_expect_end;
+mod chunk_sink;
+mod close_with_budget;
+mod assert_ordered;
+mod quota_sink;
+mod wake_all;
+mod send_all;
 
 pub use send_close::*;
+pub use send_all_close::*;
+pub use send_many::*;
+pub use flush::*;
+pub use collect_close::*;
+pub use forward_close::*;
+pub use sink_ext::*;
+pub use stream_ext::*;
+pub use drain::*;
+pub use compacting_log::*;
+pub use buffer_probe::*;
+pub use priority_queue_stream::*;
+pub use idempotent_flush_check::*;
+pub use multiplexed_connection::*;
+pub use frame_atomic_sink::*;
+pub use debounce::*;
+pub use collect_errors::*;
+pub use event_log::*;
+pub use chain_probe::*;
+pub use partitioned::*;
+pub use tumbling_window::*;
+pub use clock::*;
+pub use wal_sink::*;
+pub use incremental::*;
+pub use reopen_on_error::*;
+pub use consumer_group::*;
+pub use order_check::*;
+pub use idempotent_with_ttl::*;
+pub use livelock::*;
+pub use schema_evolve::*;
+pub use cross_order::*;
+pub use compacted_topic::*;
+pub use recovery_probe::*;
+pub use partition::*;
+pub use churn::*;
+pub use at_most_once::*;
+pub use contract_stream::*;
+pub use counting_sink::*;
+pub use vec_sink::*;
+pub use iter_stream::*;
+pub use tee::*;
+pub use strict_close::*;
+pub use close_expect_end::*;
+pub use chunk_sink::*;
+pub use close_with_budget::*;
+pub use assert_ordered::*;
+pub use quota_sink::*;
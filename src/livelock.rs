@@ -0,0 +1,127 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use futures_core::{Stream, Async};
+use futures_core::task::{Wake, Waker};
+
+use step::with_noop_context;
+
+struct WakeCount(AtomicUsize);
+
+impl Wake for WakeCount {
+    fn wake(arc_self: &Arc<WakeCount>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// The error type of `detect_livelock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LivelockError<E> {
+    /// The pipeline was polled the given number of times in a row, each time signalling a wakeup
+    /// but yielding nothing.
+    Livelock(usize),
+    /// The pipeline returned `Pending` without signalling any wakeup, so a real executor would
+    /// never poll it again.
+    Stalled,
+    /// The pipeline emitted an error.
+    Inner(E),
+}
+
+/// Drive a pipeline to completion, detecting livelock.
+///
+/// The pipeline is a stream whose items represent its net progress. It is polled again whenever
+/// it returns `Pending` after waking its task. If it does so `max_polls` times in a row without
+/// yielding an item or ending, this returns `LivelockError::Livelock`. If it returns `Pending`
+/// without waking its task, this returns `LivelockError::Stalled` instead. Otherwise, all items
+/// of the pipeline are returned once it ends.
+pub fn detect_livelock<S: Stream>(mut pipeline: S,
+                                  max_polls: usize)
+                                  -> Result<Vec<S::Item>, LivelockError<S::Error>> {
+    let wakes = Arc::new(WakeCount(AtomicUsize::new(0)));
+    let waker = Waker::from(wakes.clone());
+    let mut items = Vec::new();
+    let mut idle_polls = 0;
+
+    loop {
+        let before = wakes.0.load(Ordering::SeqCst);
+        let poll = with_noop_context(|cx| pipeline.poll_next(&mut cx.with_waker(&waker)));
+
+        match poll {
+            Ok(Async::Ready(Some(item))) => {
+                items.push(item);
+                idle_polls = 0;
+            }
+            Ok(Async::Ready(None)) => return Ok(items),
+            Ok(Async::Pending) => {
+                if wakes.0.load(Ordering::SeqCst) == before {
+                    return Err(LivelockError::Stalled);
+                }
+                idle_polls += 1;
+                if idle_polls >= max_polls {
+                    return Err(LivelockError::Livelock(idle_polls));
+                }
+            }
+            Err(err) => return Err(LivelockError::Inner(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use futures_core::Poll;
+    use futures_core::task::Context;
+    use futures::stream::iter_ok;
+    use futures::never::Never;
+
+    // Waits for its peer to become ready, waking the peer on every poll and registering itself to
+    // be woken by the peer in turn.
+    struct Component {
+        own: Rc<RefCell<Option<Waker>>>,
+        peer: Rc<RefCell<Option<Waker>>>,
+    }
+
+    impl Component {
+        fn poll(&mut self, cx: &mut Context) {
+            if let Some(waker) = self.peer.borrow_mut().take() {
+                waker.wake();
+            }
+            *self.own.borrow_mut() = Some(cx.waker());
+        }
+    }
+
+    struct Pipeline(Component, Component);
+
+    impl Stream for Pipeline {
+        type Item = ();
+        type Error = Never;
+
+        fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+            self.0.poll(cx);
+            self.1.poll(cx);
+            Ok(Async::Pending)
+        }
+    }
+
+    #[test]
+    fn detects_mutual_wakes() {
+        let a = Rc::new(RefCell::new(None));
+        let b = Rc::new(RefCell::new(None));
+        let pipeline = Pipeline(Component {
+                                    own: a.clone(),
+                                    peer: b.clone(),
+                                },
+                                Component { own: b, peer: a });
+
+        assert_eq!(detect_livelock(pipeline, 100), Err(LivelockError::Livelock(100)));
+    }
+
+    #[test]
+    fn completes_with_progress() {
+        assert_eq!(detect_livelock(iter_ok::<_, Never>(0..3), 1), Ok(vec![0, 1, 2]));
+    }
+}
@@ -0,0 +1,238 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use futures_core::{Stream, Poll, Async, Never};
+use futures_core::task::{Context, Waker};
+use futures_sink::Sink;
+
+use test_channel::{test_channel, TestSender, TestReceiver};
+use wake_all::WakeAll;
+
+enum Frame<I> {
+    Data(usize, I),
+    End(usize),
+}
+
+struct SubState<I> {
+    credits: usize,
+    buffer: VecDeque<I>,
+    ended: bool,
+    credit_waker: Option<Waker>,
+}
+
+struct Connection<I> {
+    sender: TestSender<Frame<I>, Never>,
+    receiver: TestReceiver<Frame<I>, Never>,
+    window: usize,
+    streams: Vec<SubState<I>>,
+    send_wakers: Arc<WakeAll>,
+    recv_wakers: Arc<WakeAll>,
+}
+
+impl<I> Connection<I> {
+    // Move all frames from the channel into the buffers of their sub-streams.
+    fn demultiplex(&mut self, cx: &mut Context) {
+        let waker = Waker::from(self.recv_wakers.clone());
+        let mut routed = false;
+
+        loop {
+            match self.receiver.poll_next(&mut cx.with_waker(&waker)) {
+                Ok(Async::Ready(Some(Frame::Data(id, item)))) => {
+                    self.streams[id].buffer.push_back(item);
+                    routed = true;
+                }
+                Ok(Async::Ready(Some(Frame::End(id)))) => {
+                    self.streams[id].ended = true;
+                    routed = true;
+                }
+                Ok(Async::Ready(None)) | Ok(Async::Pending) => break,
+                Err(_) => unreachable!(),
+            }
+        }
+
+        if routed {
+            waker.wake();
+        }
+    }
+}
+
+/// A simulated connection multiplexing any number of sub-streams over a single `test_channel`.
+///
+/// Each sub-stream has its own credit window: its sink may only send up to `window` items that
+/// have not yet been yielded by the corresponding stream. Frames arriving over the shared channel
+/// are always moved into per-sub-stream buffers, so a sub-stream that is blocked on credits never
+/// prevents the others from making progress.
+pub struct MultiplexedConnection<I>(Rc<RefCell<Connection<I>>>);
+
+impl<I> MultiplexedConnection<I> {
+    /// Create a new `MultiplexedConnection` over a `test_channel` of the given capacity, where each
+    /// sub-stream has a credit window of size `window`.
+    pub fn new(capacity: usize, window: usize) -> MultiplexedConnection<I> {
+        let (sender, receiver) = test_channel(capacity);
+        MultiplexedConnection(Rc::new(RefCell::new(Connection {
+                                                       sender,
+                                                       receiver,
+                                                       window,
+                                                       streams: Vec::new(),
+                                                       send_wakers: Default::default(),
+                                                       recv_wakers: Default::default(),
+                                                   })))
+    }
+
+    /// Open a new sub-stream, returning its sending and its receiving end.
+    pub fn open_stream(&self) -> (SubSink<I>, SubStream<I>) {
+        let mut connection = self.0.borrow_mut();
+        let id = connection.streams.len();
+        let credits = connection.window;
+        connection
+            .streams
+            .push(SubState {
+                      credits,
+                      buffer: VecDeque::new(),
+                      ended: false,
+                      credit_waker: None,
+                  });
+
+        (SubSink {
+             id,
+             connection: self.0.clone(),
+             end_sent: false,
+         },
+         SubStream {
+             id,
+             connection: self.0.clone(),
+         })
+    }
+}
+
+/// The sending end of a sub-stream of a `MultiplexedConnection`.
+///
+/// Closing it ends the corresponding `SubStream`, but leaves the connection open.
+pub struct SubSink<I> {
+    id: usize,
+    connection: Rc<RefCell<Connection<I>>>,
+    end_sent: bool,
+}
+
+impl<I> SubSink<I> {
+    fn poll_channel_ready(&mut self,
+                          connection: &mut Connection<I>,
+                          cx: &mut Context)
+                          -> Poll<(), Never> {
+        connection.send_wakers.register(self.id, cx.waker());
+        let waker = Waker::from(connection.send_wakers.clone());
+        connection.sender.poll_ready(&mut cx.with_waker(&waker))
+    }
+}
+
+impl<I> Sink for SubSink<I> {
+    type SinkItem = I;
+    type SinkError = Never;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        let connection = self.connection.clone();
+        let mut connection = connection.borrow_mut();
+
+        if connection.streams[self.id].credits == 0 {
+            connection.streams[self.id].credit_waker = Some(cx.waker());
+            return Ok(Async::Pending);
+        }
+
+        self.poll_channel_ready(&mut connection, cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        let mut connection = self.connection.borrow_mut();
+        connection.streams[self.id].credits -= 1;
+        connection.sender.start_send(Ok(Frame::Data(self.id, item)))
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.connection.borrow_mut().sender.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        let connection = self.connection.clone();
+        let mut connection = connection.borrow_mut();
+
+        if !self.end_sent {
+            if let Async::Pending = self.poll_channel_ready(&mut connection, cx)? {
+                return Ok(Async::Pending);
+            }
+            connection.sender.start_send(Ok(Frame::End(self.id)))?;
+            self.end_sent = true;
+        }
+
+        connection.sender.poll_flush(cx)
+    }
+}
+
+/// The receiving end of a sub-stream of a `MultiplexedConnection`.
+pub struct SubStream<I> {
+    id: usize,
+    connection: Rc<RefCell<Connection<I>>>,
+}
+
+impl<I> Stream for SubStream<I> {
+    type Item = I;
+    type Error = Never;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut connection = self.connection.borrow_mut();
+        connection.recv_wakers.register(self.id, cx.waker());
+        connection.demultiplex(cx);
+
+        let state = &mut connection.streams[self.id];
+        match state.buffer.pop_front() {
+            Some(item) => {
+                state.credits += 1;
+                if let Some(waker) = state.credit_waker.take() {
+                    waker.wake();
+                }
+                Ok(Async::Ready(Some(item)))
+            }
+            None if state.ended => Ok(Async::Ready(None)),
+            None => Ok(Async::Pending),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, StreamExt, FutureExt};
+    use futures::sink::close;
+    use futures::stream::iter_ok;
+    use futures::executor::block_on;
+
+    use step::with_noop_context;
+
+    #[test]
+    fn blocked_sub_stream_does_not_starve_others() {
+        let connection = MultiplexedConnection::new(2, 2);
+        let (mut sink_a, stream_a) = connection.open_stream();
+        let (sink_b, stream_b) = connection.open_stream();
+
+        with_noop_context(|cx| {
+            for i in 0..2 {
+                assert!(sink_a.poll_ready(cx).unwrap().is_ready());
+                sink_a.start_send(i).unwrap();
+            }
+            assert!(sink_a.poll_ready(cx).unwrap().is_pending());
+        });
+
+        let send_b = sink_b
+            .send_all(iter_ok::<_, Never>(vec![10, 11, 12, 13]))
+            .and_then(|(sink_b, _)| close(sink_b));
+        let (items_b, _) = block_on(stream_b.collect().join(send_b)).unwrap();
+        assert_eq!(items_b, vec![10, 11, 12, 13]);
+
+        with_noop_context(|cx| assert!(sink_a.poll_ready(cx).unwrap().is_pending()));
+        let (item_a, _) = block_on(stream_a.into_future()).map_err(|_| ()).unwrap();
+        assert_eq!(item_a, Some(0));
+        with_noop_context(|cx| assert!(sink_a.poll_ready(cx).unwrap().is_ready()));
+    }
+}
@@ -0,0 +1,213 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+use futures_util::future::poll_fn;
+
+use step;
+
+#[derive(Default)]
+struct CheckState {
+    last: HashMap<usize, usize>,
+    delivered: Vec<(usize, usize)>,
+}
+
+/// A sink that checks that items tagged with a sender id and a sequence number arrive in order
+/// for each sender.
+///
+/// Items are pairs `(sender, seq)`. The items of different senders may interleave arbitrarily, but
+/// the sequence numbers of each single sender must be strictly increasing. See
+/// `assert_per_sender_order`.
+pub struct OrderCheck<S> {
+    inner: S,
+    state: Rc<RefCell<CheckState>>,
+}
+
+impl<S> OrderCheck<S> {
+    /// Create a new `OrderCheck`, forwarding all checked items to `inner`.
+    pub fn new(inner: S) -> OrderCheck<S> {
+        OrderCheck {
+            inner,
+            state: Rc::new(RefCell::new(CheckState::default())),
+        }
+    }
+
+    /// Return all items that have been checked so far, in the order they arrived.
+    pub fn delivered(&self) -> Vec<(usize, usize)> {
+        self.state.borrow().delivered.clone()
+    }
+}
+
+impl<S: Sink<SinkItem = (usize, usize)>> Sink for OrderCheck<S> {
+    type SinkItem = (usize, usize);
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        {
+            let mut state = self.state.borrow_mut();
+            let (sender, seq) = item;
+            if let Some(&last) = state.last.get(&sender) {
+                if seq <= last {
+                    panic!("Sender {} delivered item {} after item {}", sender, seq, last);
+                }
+            }
+            state.last.insert(sender, seq);
+            state.delivered.push(item);
+        }
+        self.inner.start_send(item)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_close(cx)
+    }
+}
+
+/// Interleave `senders` logical senders sending `items_per_sender` items each into a shared sink,
+/// asserting that the items of each sender arrive in order, then close the sink.
+///
+/// `sink_builder` is given an `OrderCheck` wrapping a `Vec` and must return the sink under test,
+/// which forwards into the check. Sender `i` sends the items `(i, 0)`, `(i, 1)`, ... and flushes
+/// after every `i + 2` items, so the senders' sends and flushes interleave irregularly. The sink is
+/// driven with the `step` executor. Returns the items in the order they were delivered.
+///
+/// # Panics
+/// Panics if the items of some sender arrive out of order, if not all items arrived by the time
+/// closing completes, or if the sink errors.
+// `is_multiple_of` would require a much newer compiler than the rest of the crate.
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+pub fn assert_per_sender_order<S, B>(sink_builder: B,
+                                     senders: usize,
+                                     items_per_sender: usize)
+                                     -> Vec<(usize, usize)>
+    where B: FnOnce(OrderCheck<Vec<(usize, usize)>>) -> S,
+          S: Sink<SinkItem = (usize, usize)>,
+          S::SinkError: Debug
+{
+    let check = OrderCheck::new(Vec::new());
+    let state = check.state.clone();
+    let mut sink = sink_builder(check);
+    let mut sent = vec![0; senders];
+    let mut flushing = vec![false; senders];
+
+    let drive = poll_fn(|cx| -> Poll<(), S::SinkError> {
+        loop {
+            let mut progress = false;
+
+            for sender in 0..senders {
+                if flushing[sender] {
+                    if let Async::Ready(()) = sink.poll_flush(cx)? {
+                        flushing[sender] = false;
+                        progress = true;
+                    }
+                } else if sent[sender] < items_per_sender {
+                    if let Async::Ready(()) = sink.poll_ready(cx)? {
+                        sink.start_send((sender, sent[sender]))?;
+                        sent[sender] += 1;
+                        flushing[sender] = sent[sender] % (sender + 2) == 0 ||
+                                           sent[sender] == items_per_sender;
+                        progress = true;
+                    }
+                }
+            }
+
+            if sent.iter().all(|&n| n == items_per_sender) && !flushing.contains(&true) {
+                return sink.poll_close(cx);
+            }
+            if !progress {
+                return Ok(Async::Pending);
+            }
+        }
+    });
+
+    if let Err(err) = step::run(drive) {
+        panic!("Sink under test emitted an error: {:?}", err);
+    }
+
+    let delivered = state.borrow().delivered.clone();
+    if delivered.len() != senders * items_per_sender {
+        panic!("Sink delivered only {} of {} items by the time it was closed",
+               delivered.len(),
+               senders * items_per_sender);
+    }
+    delivered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Buffers all items until flushed, then forwards them in reverse order if `reverse` is set.
+    struct Batch<S: Sink> {
+        inner: S,
+        buffer: Vec<S::SinkItem>,
+        reverse: bool,
+    }
+
+    impl<S: Sink> Batch<S> {
+        fn new(inner: S, reverse: bool) -> Batch<S> {
+            Batch {
+                inner,
+                buffer: Vec::new(),
+                reverse,
+            }
+        }
+
+        fn forward_buffer(&mut self) -> Result<(), S::SinkError> {
+            if self.reverse {
+                self.buffer.reverse();
+            }
+            for item in self.buffer.drain(..) {
+                self.inner.start_send(item)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<S: Sink> Sink for Batch<S> {
+        type SinkItem = S::SinkItem;
+        type SinkError = S::SinkError;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.buffer.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.forward_buffer()?;
+            self.inner.poll_flush(cx)
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.forward_buffer()?;
+            self.inner.poll_close(cx)
+        }
+    }
+
+    #[test]
+    fn batch_preserves_per_sender_order() {
+        let delivered = assert_per_sender_order(|check| Batch::new(check, false), 2, 6);
+        assert_eq!(&delivered[..4], &[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "delivered item")]
+    fn reversing_batch_reorders() {
+        assert_per_sender_order(|check| Batch::new(check, true), 2, 6);
+    }
+}
@@ -0,0 +1,183 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::{Context, Waker};
+
+/// What a `Partition` does with items the source produces while partitioned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionPolicy {
+    /// Leave the items queued at the source, so they arrive in a burst after healing.
+    Queue,
+    /// Pull the items from the source and discard them.
+    ///
+    /// At most `MAX_PULLS_PER_POLL` items are discarded per poll. If the source has more items
+    /// ready, the stream wakes itself to continue discarding them.
+    Drop,
+}
+
+struct Split {
+    partitioned: bool,
+    waker: Option<Waker>,
+}
+
+/// A stream wrapper simulating a network partition between a source and its consumer.
+///
+/// While partitioned via its `PartitionSwitch`, the stream returns `Pending`, no matter whether the
+/// inner stream has items ready. Depending on the `PartitionPolicy`, the items produced in the
+/// meantime either stay queued at the source or are lost. The stream itself never buffers
+/// anything.
+pub struct Partition<S> {
+    inner: S,
+    policy: PartitionPolicy,
+    lost: usize,
+    // Whether the inner stream ended while its items were being dropped.
+    done: bool,
+    split: Rc<RefCell<Split>>,
+}
+
+impl<S: Stream> Partition<S> {
+    /// Create a new `Partition` with the `Queue` policy.
+    pub fn new(inner: S) -> Partition<S> {
+        Partition::with_policy(inner, PartitionPolicy::Queue)
+    }
+
+    /// Create a new `Partition` with the given policy.
+    pub fn with_policy(inner: S, policy: PartitionPolicy) -> Partition<S> {
+        Partition {
+            inner,
+            policy,
+            lost: 0,
+            done: false,
+            split: Rc::new(RefCell::new(Split {
+                                            partitioned: false,
+                                            waker: None,
+                                        })),
+        }
+    }
+
+    /// Return a switch for partitioning and healing the stream.
+    pub fn switch(&self) -> PartitionSwitch {
+        PartitionSwitch(self.split.clone())
+    }
+
+    /// Return how many items have been lost due to the `Drop` policy.
+    pub fn lost(&self) -> usize {
+        self.lost
+    }
+}
+
+impl<S: Stream> Stream for Partition<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut split = self.split.borrow_mut();
+
+        if split.partitioned {
+            if self.policy == PartitionPolicy::Drop {
+                let mut pulls = 0;
+                while !self.done {
+                    if pulls == ::MAX_PULLS_PER_POLL {
+                        cx.waker().wake();
+                        break;
+                    }
+                    pulls += 1;
+                    match self.inner.poll_next(cx)? {
+                        Async::Ready(Some(_)) => self.lost += 1,
+                        Async::Ready(None) => self.done = true,
+                        Async::Pending => break,
+                    }
+                }
+            }
+            split.waker = Some(cx.waker());
+            return Ok(Async::Pending);
+        }
+
+        if self.done {
+            Ok(Async::Ready(None))
+        } else {
+            self.inner.poll_next(cx)
+        }
+    }
+}
+
+/// The switch for partitioning and healing a `Partition` stream.
+#[derive(Clone)]
+pub struct PartitionSwitch(Rc<RefCell<Split>>);
+
+impl PartitionSwitch {
+    /// Cut delivery of items until `heal` is called.
+    pub fn partition(&self) {
+        self.0.borrow_mut().partitioned = true;
+    }
+
+    /// Resume delivery of items, waking the stream.
+    pub fn heal(&self) {
+        let mut split = self.0.borrow_mut();
+        split.partitioned = false;
+        if let Some(waker) = split.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Return whether delivery is currently cut.
+    pub fn is_partitioned(&self) -> bool {
+        self.0.borrow().partitioned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::stream::{iter_ok, repeat};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+
+    #[test]
+    fn queued_items_arrive_after_heal() {
+        let mut stream = Partition::new(iter_ok::<_, Never>(0..4));
+        let switch = stream.switch();
+
+        with_noop_context(|cx| {
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(0))));
+            switch.partition();
+            assert_eq!(stream.poll_next(cx), Ok(Async::Pending));
+            assert_eq!(stream.poll_next(cx), Ok(Async::Pending));
+        });
+
+        switch.heal();
+        assert_eq!(block_on(stream.collect()).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn dropped_items_are_lost() {
+        let mut stream = Partition::with_policy(iter_ok::<_, Never>(0..4), PartitionPolicy::Drop);
+        let switch = stream.switch();
+
+        switch.partition();
+        with_noop_context(|cx| assert_eq!(stream.poll_next(cx), Ok(Async::Pending)));
+        assert_eq!(stream.lost(), 4);
+
+        switch.heal();
+        assert_eq!(block_on(stream.collect()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn always_ready_source_is_dropped() {
+        let mut stream = Partition::with_policy(repeat::<_, Never>(0), PartitionPolicy::Drop);
+        let switch = stream.switch();
+        switch.partition();
+
+        with_noop_context(|cx| {
+            assert_eq!(stream.poll_next(cx), Ok(Async::Pending));
+            assert_eq!(stream.lost(), ::MAX_PULLS_PER_POLL);
+            switch.heal();
+            assert_eq!(stream.poll_next(cx), Ok(Async::Ready(Some(0))));
+        });
+    }
+}
@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::{Context, Waker};
+
+struct Pauses {
+    paused: Vec<bool>,
+    waker: Option<Waker>,
+}
+
+impl Pauses {
+    fn paused_mut(&mut self, partition: usize) -> &mut bool {
+        let num_partitions = self.paused.len();
+        match self.paused.get_mut(partition) {
+            Some(paused) => paused,
+            None => {
+                panic!("Partition {} does not exist, there are only {} partitions",
+                       partition,
+                       num_partitions)
+            }
+        }
+    }
+}
+
+/// A stream adapter that assigns items to simulated partitions and delivers them round-robin.
+///
+/// Whenever it is polled, items are moved from the inner stream into per-partition buffers until
+/// one of them can be delivered, the partition of an item being computed by `F` (modulo the number
+/// of partitions). A single poll pulls at most `MAX_PULLS_PER_POLL` items, and none while a
+/// partition already buffers that many, so a paused partition eventually holds back the source.
+/// Items are then yielded together with their partition id, taking turns among the partitions
+/// that have buffered items. Partitions can be paused via a `PartitionHandle`, which holds back
+/// their items until resumed.
+pub struct Partitioned<S: Stream, F> {
+    inner: S,
+    key_fn: F,
+    partitions: Vec<VecDeque<S::Item>>,
+    next: usize,
+    done: bool,
+    pauses: Rc<RefCell<Pauses>>,
+}
+
+impl<S, F> Partitioned<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> usize
+{
+    /// Create a new `Partitioned` with `num_partitions` partitions, using `key_fn` to assign
+    /// items to partitions.
+    ///
+    /// # Panics
+    /// Panics if `num_partitions` is 0.
+    pub fn with_key(inner: S, key_fn: F, num_partitions: usize) -> Partitioned<S, F> {
+        if num_partitions == 0 {
+            panic!("Partitioned must have at least one partition")
+        }
+
+        Partitioned {
+            inner,
+            key_fn,
+            partitions: (0..num_partitions).map(|_| VecDeque::new()).collect(),
+            next: 0,
+            done: false,
+            pauses: Rc::new(RefCell::new(Pauses {
+                                             paused: vec![false; num_partitions],
+                                             waker: None,
+                                         })),
+        }
+    }
+
+    /// Return a handle for pausing and resuming partitions.
+    pub fn handle(&self) -> PartitionHandle {
+        PartitionHandle(self.pauses.clone())
+    }
+}
+
+impl<S, F> Stream for Partitioned<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> usize
+{
+    type Item = (usize, S::Item);
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut pauses = self.pauses.borrow_mut();
+        let num_partitions = self.partitions.len();
+
+        // Whether an unpaused partition has a buffered item.
+        let mut deliverable = (0..num_partitions)
+            .any(|partition| !pauses.paused[partition] && !self.partitions[partition].is_empty());
+        // Whether a partition buffers the maximum number of items.
+        let mut full = self.partitions
+            .iter()
+            .any(|partition| partition.len() >= ::MAX_PULLS_PER_POLL);
+        // Whether the inner stream may have more items ready right away.
+        let mut more_ready = false;
+        let mut pulls = 0;
+        while !self.done && !deliverable && !full {
+            if pulls == ::MAX_PULLS_PER_POLL {
+                more_ready = true;
+                break;
+            }
+            pulls += 1;
+            match self.inner.poll_next(cx)? {
+                Async::Ready(Some(item)) => {
+                    let partition = (self.key_fn)(&item) % num_partitions;
+                    self.partitions[partition].push_back(item);
+                    deliverable = !pauses.paused[partition];
+                    full = self.partitions[partition].len() >= ::MAX_PULLS_PER_POLL;
+                }
+                Async::Ready(None) => self.done = true,
+                Async::Pending => break,
+            }
+        }
+
+        for i in 0..num_partitions {
+            let partition = (self.next + i) % num_partitions;
+            if pauses.paused[partition] {
+                continue;
+            }
+            if let Some(item) = self.partitions[partition].pop_front() {
+                self.next = partition + 1;
+                return Ok(Async::Ready(Some((partition, item))));
+            }
+        }
+
+        if self.done && self.partitions.iter().all(|partition| partition.is_empty()) {
+            Ok(Async::Ready(None))
+        } else {
+            if more_ready {
+                cx.waker().wake();
+            }
+            pauses.waker = Some(cx.waker());
+            Ok(Async::Pending)
+        }
+    }
+}
+
+/// The handle for pausing and resuming the partitions of a `Partitioned` stream.
+#[derive(Clone)]
+pub struct PartitionHandle(Rc<RefCell<Pauses>>);
+
+impl PartitionHandle {
+    /// Hold back the items of the given partition until it is resumed.
+    ///
+    /// # Panics
+    /// Panics if `partition` is not less than the number of partitions.
+    pub fn pause(&self, partition: usize) {
+        *self.0.borrow_mut().paused_mut(partition) = true;
+    }
+
+    /// Resume delivery of the items of the given partition, waking the stream.
+    ///
+    /// # Panics
+    /// Panics if `partition` is not less than the number of partitions.
+    pub fn resume(&self, partition: usize) {
+        let mut pauses = self.0.borrow_mut();
+        *pauses.paused_mut(partition) = false;
+        if let Some(waker) = pauses.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::stream::{iter_ok, repeat};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+
+    #[test]
+    fn paused_partition_is_held_back() {
+        let mut partitioned = Partitioned::with_key(iter_ok::<_, Never>(0..6), |&i| i, 2);
+        let handle = partitioned.handle();
+        handle.pause(1);
+
+        with_noop_context(|cx| {
+            for i in &[0, 2, 4] {
+                assert_eq!(partitioned.poll_next(cx), Ok(Async::Ready(Some((0, *i)))));
+            }
+            assert_eq!(partitioned.poll_next(cx), Ok(Async::Pending));
+        });
+
+        handle.resume(1);
+        assert_eq!(block_on(partitioned.collect()).unwrap(),
+                   vec![(1, 1), (1, 3), (1, 5)]);
+    }
+
+    #[test]
+    fn always_ready_source() {
+        let mut partitioned = Partitioned::with_key(repeat::<_, Never>(1), |&i| i, 2);
+        let handle = partitioned.handle();
+
+        with_noop_context(|cx| {
+            for _ in 0..100 {
+                assert_eq!(partitioned.poll_next(cx), Ok(Async::Ready(Some((1, 1)))));
+                assert!(partitioned.partitions[1].is_empty());
+            }
+
+            handle.pause(1);
+            for _ in 0..3 {
+                assert_eq!(partitioned.poll_next(cx), Ok(Async::Pending));
+                assert_eq!(partitioned.partitions[1].len(), ::MAX_PULLS_PER_POLL);
+            }
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "does not exist")]
+    fn pausing_nonexistent_partition_panics() {
+        let partitioned = Partitioned::with_key(iter_ok::<_, Never>(0..6), |&i| i, 2);
+        partitioned.handle().pause(2);
+    }
+}
@@ -0,0 +1,132 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures_core::{Stream, Poll, Async, Never};
+use futures_core::task::{Context, Waker};
+
+struct Queue<I> {
+    items: Vec<I>,
+    capacity: usize,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// A stream of items pushed imperatively through a `PqHandle`, always yielding the buffered item
+/// of highest priority next.
+///
+/// Priorities are computed by `F`. Items of equal priority are yielded in the order they were
+/// pushed. The stream ends once the handle has been dropped and all buffered items were yielded.
+pub struct PriorityQueueStream<I, F> {
+    queue: Rc<RefCell<Queue<I>>>,
+    priority_fn: F,
+}
+
+impl<I, P, F> PriorityQueueStream<I, F>
+    where P: Ord,
+          F: FnMut(&I) -> P
+{
+    /// Create a new `PriorityQueueStream` buffering at most `capacity` items, together with the
+    /// handle for pushing items into it.
+    pub fn new(capacity: usize, priority_fn: F) -> (PriorityQueueStream<I, F>, PqHandle<I>) {
+        let queue = Rc::new(RefCell::new(Queue {
+                                             items: Vec::new(),
+                                             capacity,
+                                             closed: false,
+                                             waker: None,
+                                         }));
+        (PriorityQueueStream {
+             queue: queue.clone(),
+             priority_fn,
+         },
+         PqHandle(queue))
+    }
+}
+
+impl<I, P, F> Stream for PriorityQueueStream<I, F>
+    where P: Ord,
+          F: FnMut(&I) -> P
+{
+    type Item = I;
+    type Error = Never;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut queue = self.queue.borrow_mut();
+
+        let mut highest: Option<(usize, P)> = None;
+        for (index, item) in queue.items.iter().enumerate() {
+            let priority = (self.priority_fn)(item);
+            let is_higher = match highest {
+                Some((_, ref highest_priority)) => priority > *highest_priority,
+                None => true,
+            };
+            if is_higher {
+                highest = Some((index, priority));
+            }
+        }
+
+        match highest {
+            Some((index, _)) => Ok(Async::Ready(Some(queue.items.remove(index)))),
+            None if queue.closed => Ok(Async::Ready(None)),
+            None => {
+                queue.waker = Some(cx.waker());
+                Ok(Async::Pending)
+            }
+        }
+    }
+}
+
+/// The handle for pushing items into a `PriorityQueueStream`.
+///
+/// Dropping the handle ends the stream once the remaining items have been yielded.
+pub struct PqHandle<I>(Rc<RefCell<Queue<I>>>);
+
+impl<I> PqHandle<I> {
+    /// Push an item into the queue, waking the stream.
+    ///
+    /// Returns the item as an error if the queue is already at capacity.
+    pub fn push(&self, item: I) -> Result<(), I> {
+        let mut queue = self.0.borrow_mut();
+        if queue.items.len() >= queue.capacity {
+            return Err(item);
+        }
+        queue.items.push(item);
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+        Ok(())
+    }
+}
+
+impl<I> Drop for PqHandle<I> {
+    fn drop(&mut self) {
+        let mut queue = self.0.borrow_mut();
+        queue.closed = true;
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::executor::block_on;
+
+    #[test]
+    fn yields_highest_priority_first() {
+        let (stream, handle) = PriorityQueueStream::new(5, |&(priority, _): &(u8, char)| priority);
+
+        assert!(handle.push((1, 'a')).is_ok());
+        assert!(handle.push((3, 'b')).is_ok());
+        assert!(handle.push((2, 'c')).is_ok());
+        assert!(handle.push((3, 'd')).is_ok());
+        assert!(handle.push((1, 'e')).is_ok());
+        assert_eq!(handle.push((5, 'f')), Err((5, 'f')));
+        drop(handle);
+
+        let items = block_on(stream.collect()).unwrap();
+        assert_eq!(items, vec![(3, 'b'), (3, 'd'), (2, 'c'), (1, 'a'), (1, 'e')]);
+    }
+}
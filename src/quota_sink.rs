@@ -0,0 +1,122 @@
+use futures_core::Poll;
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink adapter that rejects items once a quota of bytes would be exceeded, for example to model
+/// a full disk.
+///
+/// Items are forwarded to the inner sink as long as the total number of bytes stays within the
+/// quota. An item that would exceed it is not forwarded, instead `start_send` emits the configured
+/// error. Later items that still fit into the remaining quota are accepted again.
+pub struct QuotaSink<S: Sink> {
+    inner: S,
+    quota: usize,
+    accepted: usize,
+    err: S::SinkError,
+}
+
+impl<S> QuotaSink<S>
+    where S: Sink,
+          S::SinkItem: AsRef<[u8]>,
+          S::SinkError: Clone
+{
+    /// Create a new `QuotaSink`, accepting at most `quota` bytes and emitting `err` afterwards.
+    pub fn new(inner: S, quota: usize, err: S::SinkError) -> QuotaSink<S> {
+        QuotaSink {
+            inner,
+            quota,
+            accepted: 0,
+            err,
+        }
+    }
+
+    /// Return how many bytes have been accepted so far.
+    pub fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    /// Consume the `QuotaSink`, returning the inner sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Sink for QuotaSink<S>
+    where S: Sink,
+          S::SinkItem: AsRef<[u8]>,
+          S::SinkError: Clone
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        let len = item.as_ref().len();
+        if self.accepted + len > self.quota {
+            return Err(self.err.clone());
+        }
+        self.inner.start_send(item)?;
+        self.accepted += len;
+        Ok(())
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_core::Async;
+
+    use step::with_noop_context;
+
+    // Stores all items.
+    struct Disk(Vec<Vec<u8>>);
+
+    impl Sink for Disk {
+        type SinkItem = Vec<u8>;
+        type SinkError = &'static str;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.0.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    #[test]
+    fn rejects_item_crossing_quota() {
+        let mut sink = QuotaSink::new(Disk(Vec::new()), 5, "disk full");
+        with_noop_context(|cx| {
+            assert_eq!(sink.poll_ready(cx), Ok(Async::Ready(())));
+            assert_eq!(sink.start_send(vec![0; 3]), Ok(()));
+            assert_eq!(sink.poll_ready(cx), Ok(Async::Ready(())));
+            assert_eq!(sink.start_send(vec![1; 3]), Err("disk full"));
+            assert_eq!(sink.poll_ready(cx), Ok(Async::Ready(())));
+            assert_eq!(sink.start_send(vec![2; 2]), Ok(()));
+        });
+        assert_eq!(sink.accepted(), 5);
+        assert_eq!(sink.into_inner().0, vec![vec![0; 3], vec![2; 2]]);
+    }
+}
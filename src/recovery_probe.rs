@@ -0,0 +1,148 @@
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// The error type of a `RecoveryProbe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryError<E> {
+    /// The inner sink took the given number of polls to become ready again, exceeding the bound.
+    Slow(usize),
+    /// The inner sink emitted an error.
+    Inner(E),
+}
+
+/// A sink wrapper measuring how quickly the inner sink recovers from backpressure.
+///
+/// Whenever `poll_ready` of the inner sink returns `Pending` one or more times in a row and then
+/// becomes ready again, the number of `Pending` polls is recorded as a recovery time. A recovery
+/// time exceeding `max_recovery_polls` results in a `RecoveryError::Slow`.
+pub struct RecoveryProbe<S> {
+    inner: S,
+    max_recovery_polls: usize,
+    pending_polls: usize,
+    recoveries: Vec<usize>,
+}
+
+impl<S: Sink> RecoveryProbe<S> {
+    /// Create a new `RecoveryProbe`, allowing the inner sink at most `max_recovery_polls`
+    /// consecutive `Pending` polls of `poll_ready`.
+    pub fn new(inner: S, max_recovery_polls: usize) -> RecoveryProbe<S> {
+        RecoveryProbe {
+            inner,
+            max_recovery_polls,
+            pending_polls: 0,
+            recoveries: Vec::new(),
+        }
+    }
+
+    /// Return all recovery times measured so far.
+    pub fn recoveries(&self) -> &[usize] {
+        &self.recoveries
+    }
+
+    /// Consume the `RecoveryProbe`, returning the wrapped sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Sink> Sink for RecoveryProbe<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = RecoveryError<S::SinkError>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        match self.inner.poll_ready(cx).map_err(RecoveryError::Inner)? {
+            Async::Ready(()) => {
+                if self.pending_polls > 0 {
+                    self.recoveries.push(self.pending_polls);
+                    self.pending_polls = 0;
+                }
+                Ok(Async::Ready(()))
+            }
+            Async::Pending => {
+                self.pending_polls += 1;
+                if self.pending_polls > self.max_recovery_polls {
+                    Err(RecoveryError::Slow(self.pending_polls))
+                } else {
+                    Ok(Async::Pending)
+                }
+            }
+        }
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.inner.start_send(item).map_err(RecoveryError::Inner)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_flush(cx).map_err(RecoveryError::Inner)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.inner.poll_close(cx).map_err(RecoveryError::Inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::executor::block_on;
+
+    // After each item, takes `drain_polls` polls (waking itself) to become ready again.
+    struct Delay<S> {
+        inner: S,
+        drain_polls: usize,
+        remaining: usize,
+    }
+
+    impl<S: Sink> Sink for Delay<S> {
+        type SinkItem = S::SinkItem;
+        type SinkError = S::SinkError;
+
+        fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.remaining > 0 {
+                self.remaining -= 1;
+                cx.waker().wake();
+                return Ok(Async::Pending);
+            }
+            self.inner.poll_ready(cx)
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.remaining = self.drain_polls;
+            self.inner.start_send(item)
+        }
+
+        fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.inner.poll_flush(cx)
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.inner.poll_close(cx)
+        }
+    }
+
+    fn delay(drain_polls: usize) -> Delay<Vec<u8>> {
+        Delay {
+            inner: Vec::new(),
+            drain_polls,
+            remaining: 0,
+        }
+    }
+
+    #[test]
+    fn measures_drain_time() {
+        let sink = RecoveryProbe::new(delay(3), 3);
+        let sink = block_on(sink.send(0).and_then(|sink| sink.send(1))).unwrap();
+        assert_eq!(sink.recoveries(), &[3]);
+    }
+
+    #[test]
+    fn slow_recovery_errors() {
+        let sink = RecoveryProbe::new(delay(3), 2);
+        let result = block_on(sink.send(0).and_then(|sink| sink.send(1)));
+        assert_eq!(result.err(), Some(RecoveryError::Slow(3)));
+    }
+}
@@ -0,0 +1,216 @@
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink wrapper that reconstructs the inner sink whenever it errors.
+///
+/// On an error of the inner sink, the inner sink is discarded and a fresh one is created via
+/// `F`, at most `max_reopens` times in total. Once that budget is exhausted, errors are
+/// propagated.
+///
+/// The item most recently passed to `start_send` is retained until the next successful flush.
+/// If the inner sink errors before that, the item is retried on the fresh sink, so it is never
+/// silently lost across a reopen. Items sent before it (but not yet flushed) are not retried.
+pub struct ReopenOnError<S: Sink, F> {
+    inner: S,
+    factory: F,
+    reopens_left: usize,
+    reopens: usize,
+    unflushed: Option<S::SinkItem>,
+    retry: Option<S::SinkItem>,
+}
+
+impl<S, F> ReopenOnError<S, F>
+    where S: Sink,
+          S::SinkItem: Clone,
+          F: FnMut() -> S
+{
+    /// Create a new `ReopenOnError`, using `factory` to create the inner sink, reopening it at most
+    /// `max_reopens` times.
+    pub fn new(mut factory: F, max_reopens: usize) -> ReopenOnError<S, F> {
+        ReopenOnError {
+            inner: factory(),
+            factory,
+            reopens_left: max_reopens,
+            reopens: 0,
+            unflushed: None,
+            retry: None,
+        }
+    }
+
+    /// Return how many times the inner sink has been reopened.
+    pub fn reopens(&self) -> usize {
+        self.reopens
+    }
+
+    /// Get a reference to the current inner sink.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    // Replace the inner sink after an error, or propagate the error if out of reopens.
+    fn reopen(&mut self, err: S::SinkError) -> Result<(), S::SinkError> {
+        if self.reopens_left == 0 {
+            return Err(err);
+        }
+        self.reopens_left -= 1;
+        self.reopens += 1;
+        self.inner = (self.factory)();
+        self.retry = self.unflushed.take();
+        Ok(())
+    }
+
+    // Send the item to retry, if any, into the inner sink.
+    fn poll_retry(&mut self, cx: &mut Context) -> Poll<(), S::SinkError> {
+        while let Some(item) = self.retry.take() {
+            let result = match self.inner.poll_ready(cx) {
+                Ok(Async::Ready(())) => self.inner.start_send(item.clone()),
+                Ok(Async::Pending) => {
+                    self.retry = Some(item);
+                    return Ok(Async::Pending);
+                }
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(()) => self.unflushed = Some(item),
+                Err(err) => {
+                    self.unflushed = Some(item);
+                    self.reopen(err)?;
+                }
+            }
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<S, F> Sink for ReopenOnError<S, F>
+    where S: Sink,
+          S::SinkItem: Clone,
+          F: FnMut() -> S
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        loop {
+            if let Async::Pending = self.poll_retry(cx)? {
+                return Ok(Async::Pending);
+            }
+            match self.inner.poll_ready(cx) {
+                Ok(poll) => return Ok(poll),
+                Err(err) => self.reopen(err)?,
+            }
+        }
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.unflushed = Some(item.clone());
+        match self.inner.start_send(item) {
+            Ok(()) => Ok(()),
+            Err(err) => self.reopen(err),
+        }
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        loop {
+            if let Async::Pending = self.poll_retry(cx)? {
+                return Ok(Async::Pending);
+            }
+            match self.inner.poll_flush(cx) {
+                Ok(Async::Ready(())) => {
+                    self.unflushed = None;
+                    return Ok(Async::Ready(()));
+                }
+                Ok(Async::Pending) => return Ok(Async::Pending),
+                Err(err) => self.reopen(err)?,
+            }
+        }
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        loop {
+            if let Async::Pending = self.poll_retry(cx)? {
+                return Ok(Async::Pending);
+            }
+            match self.inner.poll_close(cx) {
+                Ok(Async::Ready(())) => {
+                    self.unflushed = None;
+                    return Ok(Async::Ready(()));
+                }
+                Ok(Async::Pending) => return Ok(Async::Pending),
+                Err(err) => self.reopen(err)?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::executor::block_on;
+
+    // Errors on its first flush if `failing` is set.
+    struct Flaky {
+        items: Vec<u8>,
+        failing: bool,
+    }
+
+    impl Sink for Flaky {
+        type SinkItem = u8;
+        type SinkError = ();
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.items.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.failing {
+                self.failing = false;
+                Err(())
+            } else {
+                Ok(Async::Ready(()))
+            }
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.poll_flush(cx)
+        }
+    }
+
+    fn flaky_factory() -> impl FnMut() -> Flaky {
+        let opened = Rc::new(Cell::new(0));
+        move || {
+            opened.set(opened.get() + 1);
+            Flaky {
+                items: Vec::new(),
+                failing: opened.get() == 1,
+            }
+        }
+    }
+
+    #[test]
+    fn delivers_after_reopen() {
+        let sink = ReopenOnError::new(flaky_factory(), 1);
+        let sink = block_on(sink.send(0).and_then(|sink| sink.send(1))).unwrap();
+
+        assert_eq!(sink.reopens(), 1);
+        assert_eq!(sink.get_ref().items, vec![0, 1]);
+    }
+
+    #[test]
+    fn gives_up_without_reopens() {
+        let sink = ReopenOnError::new(flaky_factory(), 0);
+        assert!(block_on(sink.send(0)).is_err());
+    }
+}
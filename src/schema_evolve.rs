@@ -0,0 +1,97 @@
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// A stream adapter simulating a change of the schema version in the middle of a stream.
+///
+/// The first `switch_at` items are transformed by `O` (the old schema), all later items by `N`
+/// (the new schema). Both transforms produce the same output type, which typically is an enum or
+/// a loosely typed representation able to hold both versions.
+pub struct SchemaEvolve<S, O, N> {
+    inner: S,
+    switch_at: usize,
+    index: usize,
+    old_transform: O,
+    new_transform: N,
+}
+
+impl<S, T, O, N> SchemaEvolve<S, O, N>
+    where S: Stream,
+          O: FnMut(S::Item) -> T,
+          N: FnMut(S::Item) -> T
+{
+    /// Create a new `SchemaEvolve`, switching from `old_transform` to `new_transform` at the item
+    /// with index `switch_at`.
+    pub fn new(inner: S,
+               switch_at: usize,
+               old_transform: O,
+               new_transform: N)
+               -> SchemaEvolve<S, O, N> {
+        SchemaEvolve {
+            inner,
+            switch_at,
+            index: 0,
+            old_transform,
+            new_transform,
+        }
+    }
+
+    /// Return whether the next item will be transformed with the new schema.
+    pub fn switched(&self) -> bool {
+        self.index >= self.switch_at
+    }
+}
+
+impl<S, T, O, N> Stream for SchemaEvolve<S, O, N>
+    where S: Stream,
+          O: FnMut(S::Item) -> T,
+          N: FnMut(S::Item) -> T
+{
+    type Item = T;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.inner.poll_next(cx)? {
+            Async::Ready(Some(item)) => {
+                let item = if self.switched() {
+                    (self.new_transform)(item)
+                } else {
+                    (self.old_transform)(item)
+                };
+                self.index += 1;
+                Ok(Async::Ready(Some(item)))
+            }
+            Async::Ready(None) => Ok(Async::Ready(None)),
+            Async::Pending => Ok(Async::Pending),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::stream::iter_ok;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    #[derive(Debug, PartialEq)]
+    enum Record {
+        V1(u8),
+        V2(u8, bool),
+    }
+
+    #[test]
+    fn switches_at_index() {
+        let stream = SchemaEvolve::new(iter_ok::<_, Never>(0..4),
+                                       2,
+                                       Record::V1,
+                                       |id| Record::V2(id, false));
+
+        assert_eq!(block_on(stream.collect()).unwrap(),
+                   vec![Record::V1(0),
+                        Record::V1(1),
+                        Record::V2(2, false),
+                        Record::V2(3, false)]);
+    }
+}
@@ -0,0 +1,69 @@
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+type Finish<S> = fn(&mut S, &mut Context) -> Poll<(), <S as Sink>::SinkError>;
+
+/// The shared core of `SendAllClose` and `SendMany`: Sends all items of an iterator down a sink,
+/// and then drives `finish` (closing or flushing the sink) to completion.
+pub struct SendAll<S: Sink, I> {
+    sink: Option<S>,
+    items: I,
+    // Set once `items` returned `None`, it is not polled again afterwards.
+    items_done: bool,
+    buffered: Option<S::SinkItem>,
+    finish: Finish<S>,
+    name: &'static str,
+}
+
+impl<S, I> SendAll<S, I>
+    where S: Sink,
+          I: Iterator<Item = S::SinkItem>
+{
+    /// Create a new `SendAll`, `name` is the name of the future used in panic messages.
+    pub fn new(sink: S, items: I, finish: Finish<S>, name: &'static str) -> SendAll<S, I> {
+        SendAll {
+            sink: Some(sink),
+            items,
+            items_done: false,
+            buffered: None,
+            finish,
+            name,
+        }
+    }
+
+    /// Poll the sending, yielding the sink once all items were sent and `finish` completed.
+    pub fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
+        {
+            let sink = match self.sink.as_mut() {
+                Some(sink) => sink,
+                None => panic!("Attempted to poll {} after completion", self.name),
+            };
+
+            loop {
+                if self.buffered.is_none() && !self.items_done {
+                    self.buffered = self.items.next();
+                    self.items_done = self.buffered.is_none();
+                }
+
+                match self.buffered.take() {
+                    Some(item) => {
+                        if let Async::Pending = sink.poll_ready(cx)? {
+                            self.buffered = Some(item);
+                            return Ok(Async::Pending);
+                        }
+                        sink.start_send(item)?;
+                    }
+                    None => {
+                        if let Async::Pending = (self.finish)(sink, cx)? {
+                            return Ok(Async::Pending);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(Async::Ready(self.sink.take().unwrap()))
+    }
+}
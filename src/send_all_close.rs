@@ -0,0 +1,193 @@
+use std::iter::Map;
+use std::vec;
+
+use futures_core::{Future, Poll, Never};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+use send_all::SendAll;
+use test_channel::TestSender;
+
+/// Future which sends all items of an iterator down a sink and then closes it.
+pub struct SendAllClose<S: Sink, I>(SendAll<S, I>);
+
+impl<S, I> SendAllClose<S, I>
+    where S: Sink,
+          I: Iterator<Item = S::SinkItem>
+{
+    /// Create a new `SendAllClose` future that sends the given items in order and then closes the
+    /// sink.
+    pub fn new<T>(sink: S, items: T) -> SendAllClose<S, I>
+        where T: IntoIterator<Item = S::SinkItem, IntoIter = I>
+    {
+        SendAllClose(SendAll::new(sink, items.into_iter(), S::poll_close, "SendAllClose"))
+    }
+}
+
+impl<S, I> Future for SendAllClose<S, I>
+    where S: Sink,
+          I: Iterator<Item = S::SinkItem>
+{
+    type Item = S;
+    type Error = S::SinkError;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
+        self.0.poll(cx)
+    }
+}
+
+/// Future which converts all items of an iterator with a function, sends them down a sink and then
+/// closes it.
+pub struct SendMapClose<S: Sink, I, F>(SendAllClose<S, Map<I, F>>);
+
+impl<S, I, F> SendMapClose<S, I, F>
+    where S: Sink,
+          I: Iterator,
+          F: FnMut(I::Item) -> S::SinkItem
+{
+    /// Create a new `SendMapClose` future that sends `f` applied to each of the given items in
+    /// order, and then closes the sink.
+    pub fn new<T>(sink: S, items: T, f: F) -> SendMapClose<S, I, F>
+        where T: IntoIterator<IntoIter = I, Item = I::Item>
+    {
+        SendMapClose(SendAllClose::new(sink, items.into_iter().map(f)))
+    }
+}
+
+impl<S, I, F> Future for SendMapClose<S, I, F>
+    where S: Sink,
+          I: Iterator,
+          F: FnMut(I::Item) -> S::SinkItem
+{
+    type Item = S;
+    type Error = S::SinkError;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
+        self.0.poll(cx)
+    }
+}
+
+/// Future which sends items and then a trailing error down a `TestSender`, and then closes it.
+///
+/// This models a stream that ends in a failure: The receiver yields the items, then the error.
+pub struct SendItemsThenErr<I, E>(SendAllClose<TestSender<I, E>, vec::IntoIter<Result<I, E>>>);
+
+impl<I, E> SendItemsThenErr<I, E> {
+    /// Create a new `SendItemsThenErr` future that sends all `items`, then `err`, and then closes
+    /// the sender.
+    pub fn new<T>(sender: TestSender<I, E>, items: T, err: E) -> SendItemsThenErr<I, E>
+        where T: IntoIterator<Item = I>
+    {
+        let mut results: Vec<_> = items.into_iter().map(Ok).collect();
+        results.push(Err(err));
+        SendItemsThenErr(SendAllClose::new(sender, results))
+    }
+}
+
+impl<I, E> Future for SendItemsThenErr<I, E> {
+    type Item = TestSender<I, E>;
+    type Error = Never;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<Self::Item, Self::Error> {
+        self.0.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_core::Async;
+
+    use futures::{StreamExt, FutureExt};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use test_channel::test_channel;
+
+    #[test]
+    fn sends_all_then_closes() {
+        let (sender, receiver) = test_channel::<u8, Never>(1);
+        let send = SendAllClose::new(sender, (0..5).map(Ok));
+
+        let (_, items) = block_on(send.join(receiver.collect())).unwrap();
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+    }
+
+    // Yields `Some` again after having returned `None`.
+    struct Unfused(bool);
+
+    impl Iterator for Unfused {
+        type Item = u8;
+
+        fn next(&mut self) -> Option<u8> {
+            self.0 = !self.0;
+            if self.0 { Some(0) } else { None }
+        }
+    }
+
+    // Closing is pending once, no items may be sent once closing started.
+    struct SlowClose {
+        items: Vec<u8>,
+        closing: bool,
+    }
+
+    impl Sink for SlowClose {
+        type SinkItem = u8;
+        type SinkError = Never;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            assert!(!self.closing, "Item sent after closing began");
+            self.items.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.closing {
+                return Ok(Async::Ready(()));
+            }
+            self.closing = true;
+            cx.waker().wake();
+            Ok(Async::Pending)
+        }
+    }
+
+    #[test]
+    fn stops_at_first_none() {
+        let sink = SlowClose {
+            items: Vec::new(),
+            closing: false,
+        };
+        let sink = block_on(SendAllClose::new(sink, Unfused(false))).unwrap();
+        assert_eq!(sink.items, vec![0]);
+    }
+
+    #[test]
+    fn maps_items() {
+        let (sender, receiver) = test_channel::<u8, Never>(1);
+        let send = SendMapClose::new(sender, 0..3, |item| Ok(item * 2));
+
+        let (_, items) = block_on(send.join(receiver.collect())).unwrap();
+        assert_eq!(items, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn ends_in_error() {
+        let (sender, receiver) = test_channel(1);
+        let send = SendItemsThenErr::new(sender, vec![0, 1], "failed");
+
+        let receive = receiver
+            .then(Ok::<_, Never>)
+            .collect();
+        let (_, results) = block_on(send.join(receive)).unwrap();
+        assert_eq!(results, vec![Ok(0), Ok(1), Err("failed")]);
+    }
+}
@@ -1,25 +1,250 @@
-use futures_core::{Future, Poll};
+use std::fmt::{self, Debug};
+
+use futures_core::{Future, Poll, Async};
 use futures_core::task::Context;
 use futures_sink::Sink;
-use futures_util::{SinkExt, FutureExt};
-use futures_util::future::AndThen;
-use futures_util::sink::{close, Close, Send};
+
+use step::{self, with_noop_context};
+
+/// The error type of a `SendClose` future.
+pub enum SendCloseError<S: Sink> {
+    /// The sink emitted an error while sending the item.
+    ///
+    /// This contains the unsent item if the sink failed before accepting it, so that it can be
+    /// sent again. It is `None` if `start_send` itself failed, since the sink consumed the item.
+    Send(S::SinkError, Option<S::SinkItem>),
+    /// The sink emitted an error while being closed, after the item has been sent.
+    Close(S::SinkError),
+}
+
+impl<S: Sink> SendCloseError<S> {
+    /// Discard the unsent item (if any) and return the error emitted by the sink.
+    pub fn into_sink_error(self) -> S::SinkError {
+        match self {
+            SendCloseError::Send(err, _) => err,
+            SendCloseError::Close(err) => err,
+        }
+    }
+}
+
+impl<S> Debug for SendCloseError<S>
+    where S: Sink,
+          S::SinkError: Debug,
+          S::SinkItem: Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SendCloseError::Send(ref err, ref item) => {
+                f.debug_tuple("Send").field(err).field(item).finish()
+            }
+            SendCloseError::Close(ref err) => f.debug_tuple("Close").field(err).finish(),
+        }
+    }
+}
 
 /// Future which sends a value down a sink and then closes it.
-pub struct SendClose<S: Sink>(AndThen<Send<S>, Close<S>, fn(S) -> Close<S>>);
+pub struct SendClose<S: Sink> {
+    sink: Option<S>,
+    item: Option<S::SinkItem>,
+}
 
 impl<S: Sink> SendClose<S> {
     /// Create a new `SendClose` future that sends the given `Item` and then closes the sink.
     pub fn new(sink: S, item: S::SinkItem) -> SendClose<S> {
-        SendClose(sink.send(item).and_then(|sink| close(sink)))
+        SendClose {
+            sink: Some(sink),
+            item: Some(item),
+        }
+    }
+
+    /// Create a new `SendClose` future that sends a clone of the given `Item` and then closes the
+    /// sink, so that the caller keeps the original.
+    pub fn new_cloned(sink: S, item: &S::SinkItem) -> SendClose<S>
+        where S::SinkItem: Clone
+    {
+        SendClose::new(sink, item.clone())
+    }
+
+    /// Get a shared reference to the sink, or `None` if the future has already completed.
+    pub fn get_ref(&self) -> Option<&S> {
+        self.sink.as_ref()
+    }
+
+    /// Get a mutable reference to the sink, or `None` if the future has already completed.
+    pub fn get_mut(&mut self) -> Option<&mut S> {
+        self.sink.as_mut()
+    }
+
+    /// Consume the future, returning the sink, or `None` if the future has already completed.
+    ///
+    /// After the future emitted an error, this recovers the sink, so that the item returned in the
+    /// error can be sent again.
+    pub fn into_inner(self) -> Option<S> {
+        self.sink
     }
 }
 
 impl<S: Sink> Future for SendClose<S> {
     type Item = S;
-    type Error = S::SinkError;
+    type Error = SendCloseError<S>;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, SendCloseError<S>> {
+        {
+            let sink = self.sink
+                .as_mut()
+                .expect("Attempted to poll SendClose after completion");
+
+            if let Some(item) = self.item.take() {
+                match sink.poll_ready(cx) {
+                    Ok(Async::Ready(())) => {}
+                    Ok(Async::Pending) => {
+                        self.item = Some(item);
+                        return Ok(Async::Pending);
+                    }
+                    Err(err) => return Err(SendCloseError::Send(err, Some(item))),
+                }
+                sink.start_send(item)
+                    .map_err(|err| SendCloseError::Send(err, None))?;
+            }
+
+            if let Async::Pending = sink.poll_close(cx).map_err(SendCloseError::Close)? {
+                return Ok(Async::Pending);
+            }
+        }
+
+        Ok(Async::Ready(self.sink.take().unwrap()))
+    }
+}
+
+/// Run a `SendClose` future to completion with the `step` executor, asserting that the item was
+/// forwarded and that the sink is left in a closed state.
+///
+/// After the future completed, `last_received` is called to find out the last item that arrived at
+/// the sink's destination (e.g. the last item of a `VecSink`, or the last item of the receiver of a
+/// `TestSender`), which must be equal to `item`. Then the sink is closed once more, which must
+/// complete immediately. Returns the closed sink.
+///
+/// # Panics
+/// Panics if the sink errors, if `last_received` does not return the item, or if closing the sink
+/// again does not complete immediately.
+pub fn assert_send_close_completes<S, F>(sink: S, item: S::SinkItem, last_received: F) -> S
+    where S: Sink,
+          S::SinkError: Debug,
+          S::SinkItem: Clone + PartialEq + Debug,
+          F: FnOnce(&S) -> Option<S::SinkItem>
+{
+    let mut sink = match step::run(SendClose::new(sink, item.clone())) {
+        Ok(sink) => sink,
+        Err(err) => panic!("Sink emitted an error during SendClose: {:?}", err),
+    };
+
+    match last_received(&sink) {
+        Some(ref received) if *received == item => {}
+        received => {
+            panic!("SendClose did not forward {:?}, the last received item was {:?}",
+                   item,
+                   received)
+        }
+    }
+
+    match with_noop_context(|cx| sink.poll_close(cx)) {
+        Ok(Async::Ready(())) => sink,
+        Ok(Async::Pending) => panic!("Sink was not closed after SendClose completed"),
+        Err(err) => panic!("Sink emitted an error when closed again: {:?}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_core::Stream;
+    use futures::StreamExt;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::poll_once;
+    use test_channel::test_channel;
+
+    #[test]
+    fn vec_is_closed_with_item() {
+        let sink = assert_send_close_completes(vec![0], 1, |sink| sink.last().cloned());
+        assert_eq!(sink, vec![0, 1]);
+    }
+
+    #[test]
+    fn sends_clone() {
+        let item = String::from("item");
+        let sink = block_on(SendClose::new_cloned(Vec::new(), &item)).unwrap();
+        assert_eq!(sink, vec![item]);
+    }
+
+    #[test]
+    fn test_sender_is_closed_with_item() {
+        let (sender, mut receiver) = test_channel::<u8, Never>(1);
+        assert_send_close_completes(sender, Ok(0), |_| {
+            match with_noop_context(|cx| receiver.poll_next(cx)) {
+                Ok(Async::Ready(item)) => item.map(Ok),
+                _ => None,
+            }
+        });
+        assert_eq!(block_on(receiver.collect()).unwrap(), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "last received item")]
+    fn detects_missing_item() {
+        assert_send_close_completes(Vec::new(), 0, |_| None);
+    }
+
+    // Rejects the first item before accepting it, and accepts everything afterwards.
+    struct RejectingOnce {
+        rejected: bool,
+        items: Vec<u8>,
+    }
+
+    impl Sink for RejectingOnce {
+        type SinkItem = u8;
+        type SinkError = ();
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            if self.rejected {
+                Ok(Async::Ready(()))
+            } else {
+                self.rejected = true;
+                Err(())
+            }
+        }
+
+        fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+            self.items.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    #[test]
+    fn failed_send_can_be_retried() {
+        let sink = RejectingOnce {
+            rejected: false,
+            items: Vec::new(),
+        };
+        let mut send = SendClose::new(sink, 42);
+        let item = match poll_once(&mut send) {
+            Err(SendCloseError::Send((), Some(item))) => item,
+            Err(err) => panic!("Unexpected error: {:?}", err),
+            Ok(_) => panic!("SendClose did not fail"),
+        };
 
-    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
-        self.0.poll(cx)
+        let sink = send.into_inner().unwrap();
+        let sink = block_on(SendClose::new(sink, item)).unwrap();
+        assert_eq!(sink.items, vec![42]);
     }
 }
@@ -0,0 +1,57 @@
+use futures_core::{Future, Poll};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+use send_all::SendAll;
+
+/// Future which sends all items of an iterator down a sink and then flushes it, but leaves it open.
+///
+/// Unlike `SendAllClose`, this yields a sink that can still be used for sending further items.
+pub struct SendMany<S: Sink, I>(SendAll<S, I>);
+
+impl<S, I> SendMany<S, I>
+    where S: Sink,
+          I: Iterator<Item = S::SinkItem>
+{
+    /// Create a new `SendMany` future that sends the given items in order and then flushes the
+    /// sink.
+    pub fn new<T>(sink: S, items: T) -> SendMany<S, I>
+        where T: IntoIterator<Item = S::SinkItem, IntoIter = I>
+    {
+        SendMany(SendAll::new(sink, items.into_iter(), S::poll_flush, "SendMany"))
+    }
+}
+
+impl<S, I> Future for SendMany<S, I>
+    where S: Sink,
+          I: Iterator<Item = S::SinkItem>
+{
+    type Item = S;
+    type Error = S::SinkError;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
+        self.0.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{StreamExt, FutureExt};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use send_all_close::SendAllClose;
+    use test_channel::test_channel;
+
+    #[test]
+    fn leaves_sink_open() {
+        let (sender, receiver) = test_channel::<u8, Never>(1);
+        let send = SendMany::new(sender, (0..3).map(Ok))
+            .and_then(|sender| SendAllClose::new(sender, (3..5).map(Ok)));
+
+        let (_, items) = block_on(send.join(receiver.collect())).unwrap();
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+    }
+}
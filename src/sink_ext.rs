@@ -0,0 +1,68 @@
+use futures_sink::Sink;
+use futures_util::sink::{close, Close};
+
+use flush::{Flush, FlushClose};
+use send_close::SendClose;
+use send_many::SendMany;
+
+/// An extension trait for sinks, providing methods that return the futures of this crate.
+pub trait AtmSinkExt: Sink {
+    /// Send the given item down this sink and then close it, see `SendClose`.
+    fn send_close(self, item: Self::SinkItem) -> SendClose<Self>
+        where Self: Sized
+    {
+        SendClose::new(self, item)
+    }
+
+    /// Send all given items down this sink and flush it, leaving it open, see `SendMany`.
+    fn send_many<T>(self, items: T) -> SendMany<Self, T::IntoIter>
+        where Self: Sized,
+              T: IntoIterator<Item = Self::SinkItem>
+    {
+        SendMany::new(self, items)
+    }
+
+    /// Flush this sink, yielding it back afterwards, see `Flush`.
+    fn flush_future(self) -> Flush<Self>
+        where Self: Sized
+    {
+        Flush::new(self)
+    }
+
+    /// Flush and then close this sink, yielding it back afterwards, see `FlushClose`.
+    fn flush_close(self) -> FlushClose<Self>
+        where Self: Sized
+    {
+        FlushClose::new(self)
+    }
+
+    /// Close this sink, yielding it back afterwards.
+    fn close_future(self) -> Close<Self>
+        where Self: Sized
+    {
+        close(self)
+    }
+}
+
+impl<S: Sink> AtmSinkExt for S {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::FutureExt;
+
+    use send_close::SendCloseError;
+    use futures::executor::block_on;
+
+    #[test]
+    fn chains_crate_futures() {
+        let sink = block_on(Vec::new()
+                                .send_close(0)
+                                .map_err(SendCloseError::into_sink_error)
+                                .and_then(|sink| sink.flush_future())
+                                .and_then(|sink| sink.close_future()))
+                .unwrap();
+        assert_eq!(sink, vec![0]);
+    }
+}
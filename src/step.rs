@@ -0,0 +1,128 @@
+//! A minimal executor for driving futures by hand, one poll at a time.
+//!
+//! Every poll uses a waker that does nothing, so wakeups are ignored and it is up to the caller
+//! to decide when to poll again. This makes the interleaving of polls fully deterministic.
+
+use std::sync::Arc;
+
+use futures_core::{Future, Poll, Async, Never};
+use futures_core::executor::{Executor, SpawnError};
+use futures_core::task::{Context, LocalMap, Wake, Waker};
+
+struct NoopWake;
+
+impl Wake for NoopWake {
+    fn wake(_: &Arc<NoopWake>) {}
+}
+
+struct NoopExecutor;
+
+impl Executor for NoopExecutor {
+    fn spawn(&mut self,
+             _: Box<dyn Future<Item = (), Error = Never> + Send>)
+             -> Result<(), SpawnError> {
+        Err(SpawnError::shutdown())
+    }
+}
+
+/// Create a `Waker` that does nothing when woken.
+pub fn noop_waker() -> Waker {
+    Waker::from(Arc::new(NoopWake))
+}
+
+/// Call `f` with a fresh task context whose waker does nothing.
+///
+/// The context's executor refuses to spawn any tasks.
+pub fn with_noop_context<T, F>(f: F) -> T
+    where F: FnOnce(&mut Context) -> T
+{
+    let mut map = LocalMap::new();
+    let waker = noop_waker();
+    let mut exec = NoopExecutor;
+    let mut cx = Context::new(&mut map, &waker, &mut exec);
+    f(&mut cx)
+}
+
+/// Poll the given future exactly once.
+pub fn poll_once<F: Future>(future: &mut F) -> Poll<F::Item, F::Error> {
+    with_noop_context(|cx| future.poll(cx))
+}
+
+/// Poll the given future until it completes.
+///
+/// Since wakeups are ignored, the future is simply polled again whenever it returns `Pending`.
+/// This never terminates if the future never completes.
+pub fn run<F: Future>(mut future: F) -> Result<F::Item, F::Error> {
+    loop {
+        if let Async::Ready(item) = poll_once(&mut future)? {
+            return Ok(item);
+        }
+    }
+}
+
+/// Drives a sink-side future and a stream-side future in lockstep, polling each only when told to.
+///
+/// This allows tests to orchestrate the exact interleaving of producer and consumer polls.
+pub struct Stepper<A, B> {
+    sink: A,
+    stream: B,
+}
+
+impl<A: Future, B: Future> Stepper<A, B> {
+    /// Create a new `Stepper` from the future driving the sink and the future driving the stream.
+    pub fn new(sink: A, stream: B) -> Stepper<A, B> {
+        Stepper { sink, stream }
+    }
+
+    /// Poll the sink-side future exactly once.
+    pub fn step_sink(&mut self) -> Poll<A::Item, A::Error> {
+        poll_once(&mut self.sink)
+    }
+
+    /// Poll the stream-side future exactly once.
+    pub fn step_stream(&mut self) -> Poll<B::Item, B::Error> {
+        poll_once(&mut self.stream)
+    }
+
+    /// Consume the `Stepper`, returning the two futures.
+    pub fn into_inner(self) -> (A, B) {
+        (self.sink, self.stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, StreamExt, FutureExt};
+    use futures::sink::close;
+    use futures::stream::iter_ok;
+
+    use test_channel::*;
+
+    #[test]
+    fn runs_to_completion() {
+        let (sender, receiver) = test_channel::<u8, Never>(1);
+
+        let send_stuff = sender
+            .send_all(iter_ok::<_, Never>(vec![Ok(0), Ok(1), Ok(2)]))
+            .and_then(|(sender, _)| close(sender).map(|_| ()));
+
+        let (items, _) = run(receiver.collect().join(send_stuff)).unwrap();
+        assert_eq!(items, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn stream_before_sink() {
+        let (sender, receiver) = test_channel::<u8, Never>(1);
+        let mut stepper = Stepper::new(sender.send(Ok(42)), receiver.into_future());
+
+        assert!(stepper.step_stream().map_err(|_| ()).unwrap().is_pending());
+        assert!(stepper.step_sink().unwrap().is_ready());
+
+        match stepper.step_stream() {
+            Ok(Async::Ready((item, _))) => assert_eq!(item, Some(42)),
+            _ => panic!("Expected the stream to yield the sent item"),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use futures_core::{Future, Stream, Poll, Async};
+use futures_core::task::Context;
+
+use drain::Drain;
+
+/// Future which collects at most a fixed number of items from a stream.
+///
+/// It completes once the stream ended or the limit was reached, whichever happens first. The
+/// stream is not polled any further after the limit was reached.
+pub struct CollectAtMost<S: Stream> {
+    stream: S,
+    n: usize,
+    items: Option<Vec<S::Item>>,
+}
+
+impl<S: Stream> CollectAtMost<S> {
+    /// Create a new `CollectAtMost` future collecting at most `n` items from `stream`.
+    pub fn new(stream: S, n: usize) -> CollectAtMost<S> {
+        CollectAtMost {
+            stream,
+            n,
+            items: Some(Vec::new()),
+        }
+    }
+}
+
+impl<S: Stream> Future for CollectAtMost<S> {
+    type Item = Vec<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<Self::Item, Self::Error> {
+        let mut items = self.items
+            .take()
+            .expect("Attempted to poll CollectAtMost after completion");
+
+        while items.len() < self.n {
+            match self.stream.poll_next(cx) {
+                Ok(Async::Ready(Some(item))) => items.push(item),
+                Ok(Async::Ready(None)) => break,
+                Ok(Async::Pending) => {
+                    self.items = Some(items);
+                    return Ok(Async::Pending);
+                }
+                Err(err) => {
+                    self.items = Some(items);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(Async::Ready(items))
+    }
+}
+
+/// An extension trait for streams, providing methods that return the futures of this crate.
+pub trait AtmStreamExt: Stream {
+    /// Collect at most `n` items of this stream, see `CollectAtMost`.
+    fn collect_at_most(self, n: usize) -> CollectAtMost<Self>
+        where Self: Sized
+    {
+        CollectAtMost::new(self, n)
+    }
+
+    /// Read this stream to completion, discarding all items, see `Drain`.
+    fn drain(self) -> Drain<Self>
+        where Self: Sized
+    {
+        Drain::new(self)
+    }
+}
+
+impl<S: Stream> AtmStreamExt for S {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::stream::repeat;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::poll_once;
+
+    #[test]
+    fn stops_at_limit() {
+        let items = block_on(repeat::<_, Never>(7).collect_at_most(3)).unwrap();
+        assert_eq!(items, vec![7, 7, 7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "after completion")]
+    fn panics_when_polled_after_completion() {
+        let mut collect = repeat::<_, Never>(7).collect_at_most(1);
+        assert_eq!(poll_once(&mut collect), Ok(Async::Ready(vec![7])));
+        let _ = poll_once(&mut collect);
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Debug;
+
+use futures_core::{Future, Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// Future which closes a sink like `futures::sink::Close`, but also checks that closing is
+/// idempotent.
+///
+/// Once closing the sink completed, it is closed one more time, which must complete immediately
+/// again. This check is only performed when debug assertions are enabled.
+///
+/// # Panics
+/// Panics (with debug assertions enabled) if closing the sink a second time does not complete
+/// immediately.
+pub struct StrictClose<S> {
+    sink: Option<S>,
+}
+
+impl<S: Sink> StrictClose<S> {
+    /// Create a new `StrictClose` future that closes the given sink.
+    ///
+    /// Since `futures::sink::Close` belongs to another crate, this stands in for a
+    /// `Close::strict(sink)` constructor.
+    pub fn new(sink: S) -> StrictClose<S> {
+        StrictClose { sink: Some(sink) }
+    }
+}
+
+impl<S> Future for StrictClose<S>
+    where S: Sink,
+          S::SinkError: Debug
+{
+    type Item = S;
+    type Error = S::SinkError;
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<S, S::SinkError> {
+        let mut sink = self.sink
+            .take()
+            .expect("Attempted to poll StrictClose after completion");
+
+        if let Async::Pending = sink.poll_close(cx)? {
+            self.sink = Some(sink);
+            return Ok(Async::Pending);
+        }
+
+        if cfg!(debug_assertions) {
+            match sink.poll_close(cx) {
+                Ok(Async::Ready(())) => {}
+                Ok(Async::Pending) => panic!("Sink was not closed anymore when closed again"),
+                Err(err) => panic!("Sink emitted an error when closed again: {:?}", err),
+            }
+        }
+        Ok(Async::Ready(sink))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use vec_sink::VecSink;
+
+    // Only completes every other close.
+    struct Flaky(bool);
+
+    impl Sink for Flaky {
+        type SinkItem = ();
+        type SinkError = Never;
+
+        fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn start_send(&mut self, _: Self::SinkItem) -> Result<(), Self::SinkError> {
+            Ok(())
+        }
+
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+            Ok(Async::Ready(()))
+        }
+
+        fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+            self.0 = !self.0;
+            if self.0 {
+                Ok(Async::Ready(()))
+            } else {
+                cx.waker().wake();
+                Ok(Async::Pending)
+            }
+        }
+    }
+
+    #[test]
+    fn closes_compliant_sink() {
+        block_on(StrictClose::new(VecSink::<u8>::new())).unwrap();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "not closed anymore")]
+    fn detects_non_idempotent_close() {
+        let _ = block_on(StrictClose::new(Flaky(false)));
+    }
+}
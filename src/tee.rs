@@ -0,0 +1,94 @@
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+use test_channel::{unbounded_test_channel, TestSender, TestReceiver};
+
+/// Duplicate a stream for observation: The returned `Tee` behaves like `stream`, while the returned
+/// `TestReceiver` yields a copy of every item and error it emits.
+///
+/// The copies are sent over an unbounded channel, so the observer never slows down the tee. When
+/// the stream ends, the channel is closed, and the receiver ends as well. If the receiver is
+/// dropped, the tee keeps working and simply stops making copies.
+pub fn tee<S>(stream: S) -> (Tee<S>, TestReceiver<S::Item, S::Error>)
+    where S: Stream,
+          S::Item: Clone,
+          S::Error: Clone
+{
+    let (sender, receiver) = unbounded_test_channel();
+    (Tee { stream, sender }, receiver)
+}
+
+/// The stream returned by `tee`.
+pub struct Tee<S: Stream> {
+    stream: S,
+    sender: TestSender<S::Item, S::Error>,
+}
+
+impl<S: Stream> Tee<S> {
+    fn copy(&mut self, result: Result<S::Item, S::Error>) {
+        if !self.sender.is_closed() {
+            self.sender
+                .try_send(result)
+                .expect("Unbounded channel of Tee is never full");
+        }
+    }
+}
+
+impl<S> Stream for Tee<S>
+    where S: Stream,
+          S::Item: Clone,
+          S::Error: Clone
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.stream.poll_next(cx) {
+            Ok(Async::Ready(Some(item))) => {
+                self.copy(Ok(item.clone()));
+                Ok(Async::Ready(Some(item)))
+            }
+            Ok(Async::Ready(None)) => {
+                if !self.sender.is_closed() {
+                    let _ = self.sender.poll_close(cx);
+                }
+                Ok(Async::Ready(None))
+            }
+            Ok(Async::Pending) => Ok(Async::Pending),
+            Err(err) => {
+                self.copy(Err(err.clone()));
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{StreamExt, FutureExt};
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use iter_stream::IterStream;
+
+    #[test]
+    fn observer_sees_everything() {
+        let (tee, observer) = tee(IterStream::new(vec![Ok(0), Err("failed"), Ok(1)]));
+
+        let output = tee.then(Ok::<_, Never>).collect();
+        let observed = observer.then(Ok::<_, Never>).collect();
+        let (output, observed) = block_on(output.join(observed)).unwrap();
+        assert_eq!(output, vec![Ok(0), Err("failed"), Ok(1)]);
+        assert_eq!(observed, output);
+    }
+
+    #[test]
+    fn works_without_observer() {
+        let (tee, observer) = tee(IterStream::new(vec![Ok::<_, Never>(0), Ok(1)]));
+        drop(observer);
+        assert_eq!(block_on(tee.collect()).unwrap(), vec![0, 1]);
+    }
+}
@@ -1,33 +1,250 @@
 //! An in-memory channel for testing purposes. Allows sending items and errors to a receiver.
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
 use futures_core::{Stream, Poll, Async, Never};
-use futures_core::task::Context;
+use futures_core::task::{Context, AtomicWaker};
 use futures_sink::Sink;
-use futures_channel::mpsc::{channel, Sender, Receiver};
+use futures_channel::mpsc::{channel, unbounded, Sender, Receiver, UnboundedSender,
+                            UnboundedReceiver, SendError, TrySendError};
+use futures_util::StreamExt;
+use futures_util::stream::{Map, MapErr, Take};
 
 /// Create a test channel of a given capacity.
 ///
 /// `I` is the type of items sent over the channel, `E` is the type of errors sent over the channel.
 ///
-/// # Panics
-/// Panics if the given capacity is 0.
+/// A capacity of 0 creates a rendezvous channel: The sender can not be flushed (or closed) until
+/// the receiver has taken the item it sent, and it can not send another item before that either.
 pub fn test_channel<I, E>(capacity: usize) -> (TestSender<I, E>, TestReceiver<I, E>) {
-    if capacity == 0 {
-        panic!("TestChannel must have capacity greater than 0")
+    let (sender, receiver) = channel(capacity.saturating_sub(1));
+    let rendezvous = if capacity == 0 {
+        Some(Arc::new(Rendezvous::default()))
+    } else {
+        None
+    };
+    new_pair(SenderInner::Bounded(sender),
+             ReceiverInner::Bounded(receiver),
+             capacity,
+             rendezvous)
+}
+
+/// Create a test channel of a given capacity, whose receiver reports a premature disconnect as an
+/// error.
+///
+/// If the sender is dropped without having been closed, the receiver yields `disconnect_err` once
+/// (after all items sent before), and ends afterwards. If the sender is closed, the receiver ends
+/// normally.
+pub fn test_channel_erroring_on_disconnect<I, E>(capacity: usize,
+                                                 disconnect_err: E)
+                                                 -> (TestSender<I, E>, TestReceiver<I, E>) {
+    let (sender, mut receiver) = test_channel(capacity);
+    receiver.disconnect_err = Some(disconnect_err);
+    (sender, receiver)
+}
+
+/// Create a test channel without a capacity limit, so that sending never blocks.
+///
+/// `I` is the type of items sent over the channel, `E` is the type of errors sent over the channel.
+pub fn unbounded_test_channel<I, E>() -> (TestSender<I, E>, TestReceiver<I, E>) {
+    let (sender, receiver) = unbounded();
+    new_pair(SenderInner::Unbounded(sender),
+             ReceiverInner::Unbounded(receiver),
+             usize::MAX,
+             None)
+}
+
+fn new_pair<I, E>(sender: SenderInner<Result<I, E>>,
+                  receiver: ReceiverInner<Result<I, E>>,
+                  capacity: usize,
+                  rendezvous: Option<Arc<Rendezvous>>)
+                  -> (TestSender<I, E>, TestReceiver<I, E>) {
+    let closed = Arc::new(AtomicBool::new(false));
+    let dropped = Arc::new(AtomicWaker::new());
+    (TestSender {
+         inner: sender,
+         capacity,
+         closed: closed.clone(),
+         dropped: dropped.clone(),
+         rendezvous: rendezvous.clone(),
+     },
+     TestReceiver {
+         inner: receiver,
+         closed,
+         dropped,
+         disconnect_err: None,
+         rendezvous,
+     })
+}
+
+// State shared by both ends of a rendezvous channel.
+#[derive(Default)]
+struct Rendezvous {
+    // The number of items sent but not yet taken by the receiver.
+    in_flight: AtomicUsize,
+    // Woken whenever the receiver takes an item (or is dropped).
+    waker: AtomicWaker,
+}
+
+impl Rendezvous {
+    fn take(&self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.waker.wake();
     }
-    let (sender, receiver) = channel(capacity - 1);
-    (TestSender::new(sender), TestReceiver::new(receiver))
+}
+
+enum SenderInner<T> {
+    Bounded(Sender<T>),
+    Unbounded(UnboundedSender<T>),
+}
+
+enum ReceiverInner<T> {
+    Bounded(Receiver<T>),
+    Unbounded(UnboundedReceiver<T>),
 }
 
 /// The transmission end of a test channel.
 ///
-/// This is built upon `futures::channel::mpcs::sender` and panics if the underlying `Sender` emits
-/// an error.
-pub struct TestSender<I, E>(Sender<Result<I, E>>);
+/// This is built upon `futures::channel::mpcs::sender` (or `UnboundedSender` for an unbounded
+/// channel) and panics if the underlying sender emits an error.
+///
+/// A `TestSender` is `Send` and `Sync` whenever both `I` and `E` are `Send`.
+pub struct TestSender<I, E> {
+    inner: SenderInner<Result<I, E>>,
+    capacity: usize,
+    // Set once the sender has been closed, as opposed to just dropped.
+    closed: Arc<AtomicBool>,
+    // Woken when the receiver is dropped.
+    dropped: Arc<AtomicWaker>,
+    rendezvous: Option<Arc<Rendezvous>>,
+}
 
 impl<I, E> TestSender<I, E> {
-    fn new(sender: Sender<Result<I, E>>) -> TestSender<I, E> {
-        TestSender(sender)
+    /// Return the capacity the channel was created with, or `usize::MAX` for an unbounded channel.
+    ///
+    /// This many items can be sent before sending blocks, if the receiver does not take any. The
+    /// exception is a rendezvous channel with a capacity of 0, whose sender can hand off a single
+    /// item and then blocks until the receiver has taken it.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return whether the channel has been closed, either because the receiver has been dropped, or
+    /// because this sender has been closed.
+    pub fn is_closed(&self) -> bool {
+        match self.inner {
+            SenderInner::Bounded(ref sender) => sender.is_closed(),
+            SenderInner::Unbounded(ref sender) => sender.is_closed(),
+        }
+    }
+
+    /// Poll whether the channel has been closed, see `is_closed`.
+    ///
+    /// If it has not, the current task is woken once the receiver is dropped, so that a producer
+    /// can wait for the receiver to go away without having to send anything.
+    pub fn poll_canceled(&self, cx: &mut Context) -> Async<()> {
+        if self.is_closed() {
+            return Async::Ready(());
+        }
+        self.dropped.register(cx.waker());
+        if self.is_closed() {
+            Async::Ready(())
+        } else {
+            Async::Pending
+        }
+    }
+
+    // For a rendezvous channel, wait until the receiver took all items sent so far.
+    fn poll_rendezvous(&self, cx: &mut Context) -> Async<()> {
+        if let Some(ref rendezvous) = self.rendezvous {
+            if rendezvous.in_flight.load(Ordering::SeqCst) > 0 {
+                rendezvous.waker.register(cx.waker());
+                if rendezvous.in_flight.load(Ordering::SeqCst) > 0 {
+                    return Async::Pending;
+                }
+            }
+        }
+        Async::Ready(())
+    }
+
+    // Must be called before handing an item to the inner sender, so that the receiver can not take
+    // it before it is counted.
+    fn start_rendezvous(&self) {
+        if let Some(ref rendezvous) = self.rendezvous {
+            rendezvous.in_flight.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Attempt to send an item without blocking, for use outside of any task.
+    ///
+    /// Returns an error if the channel is full, from which the item can be retrieved again.
+    ///
+    /// # Panics
+    /// Panics if the receiver has been dropped, like all other sending methods.
+    pub fn try_send(&mut self, item: Result<I, E>) -> Result<(), TrySendError<Result<I, E>>> {
+        self.start_rendezvous();
+        let result = match self.inner {
+            SenderInner::Bounded(ref mut sender) => sender.try_send(item),
+            SenderInner::Unbounded(ref sender) => sender.unbounded_send(item),
+        };
+        match result {
+            Err(ref err) if err.is_disconnected() => {
+                panic!("TestSender got a send error: {:?}", err)
+            }
+            Err(err) => {
+                if let Some(ref rendezvous) = self.rendezvous {
+                    rendezvous.in_flight.fetch_sub(1, Ordering::SeqCst);
+                }
+                Err(err)
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+}
+
+// The fallible sink methods, `TestSender` panics on their errors, `TestSenderResult` forwards them.
+impl<I, E> TestSender<I, E> {
+    fn try_poll_ready(&mut self, cx: &mut Context) -> Poll<(), SendError> {
+        if self.poll_rendezvous(cx).is_pending() {
+            return Ok(Async::Pending);
+        }
+        match self.inner {
+            SenderInner::Bounded(ref mut sender) => sender.poll_ready(cx),
+            SenderInner::Unbounded(ref mut sender) => sender.poll_ready(cx),
+        }
+    }
+
+    fn try_start_send(&mut self, item: Result<I, E>) -> Result<(), SendError> {
+        self.start_rendezvous();
+        match self.inner {
+            SenderInner::Bounded(ref mut sender) => sender.start_send(item),
+            SenderInner::Unbounded(ref mut sender) => sender.start_send(item),
+        }
+    }
+
+    fn try_poll_flush(&mut self, cx: &mut Context) -> Poll<(), SendError> {
+        if self.poll_rendezvous(cx).is_pending() {
+            return Ok(Async::Pending);
+        }
+        match self.inner {
+            SenderInner::Bounded(ref mut sender) => sender.poll_flush(cx),
+            SenderInner::Unbounded(ref mut sender) => sender.poll_flush(cx),
+        }
+    }
+
+    fn try_poll_close(&mut self, cx: &mut Context) -> Poll<(), SendError> {
+        if self.poll_rendezvous(cx).is_pending() {
+            return Ok(Async::Pending);
+        }
+        let result = match self.inner {
+            SenderInner::Bounded(ref mut sender) => sender.poll_close(cx),
+            SenderInner::Unbounded(ref mut sender) => sender.poll_close(cx),
+        };
+        if let Ok(Async::Ready(())) = result {
+            self.closed.store(true, Ordering::SeqCst);
+        }
+        result
     }
 }
 
@@ -36,40 +253,125 @@ impl<I, E> Sink for TestSender<I, E> {
     type SinkError = Never;
 
     fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
-        match self.0.poll_ready(cx) {
+        match self.try_poll_ready(cx) {
             Err(err) => panic!("TestSender got a send error: {:?}", err),
             Ok(non_err) => Ok(non_err),
         }
     }
 
     fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
-        match self.0.start_send(item) {
+        match self.try_start_send(item) {
             Err(err) => panic!("TestSender got a send error: {:?}", err),
             Ok(non_err) => Ok(non_err),
         }
     }
 
     fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
-        match self.0.poll_flush(cx) {
+        match self.try_poll_flush(cx) {
             Err(err) => panic!("TestSender got a send error: {:?}", err),
             Ok(non_err) => Ok(non_err),
         }
     }
 
     fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
-        match self.0.poll_close(cx) {
+        match self.try_poll_close(cx) {
             Err(err) => panic!("TestSender got a send error: {:?}", err),
             Ok(non_err) => Ok(non_err),
         }
     }
 }
 
+/// Create a test channel of a given capacity, whose sender emits errors instead of panicking.
+///
+/// This behaves like `test_channel`, except that the sender reports the errors of the underlying
+/// channel (i.e. a dropped receiver) as a `SendError`.
+pub fn test_channel_no_panic<I, E>(capacity: usize)
+                                   -> (TestSenderResult<I, E>, TestReceiver<I, E>) {
+    let (sender, receiver) = test_channel(capacity);
+    (TestSenderResult(sender), receiver)
+}
+
+/// The transmission end of a test channel created via `test_channel_no_panic`.
+///
+/// Unlike a `TestSender`, this emits the errors of the underlying channel rather than panicking.
+pub struct TestSenderResult<I, E>(TestSender<I, E>);
+
+impl<I, E> TestSenderResult<I, E> {
+    /// Return the capacity the channel was created with.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Return whether the channel has been closed, see `TestSender::is_closed`.
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+impl<I, E> Sink for TestSenderResult<I, E> {
+    type SinkItem = Result<I, E>;
+    type SinkError = SendError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.0.try_poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.0.try_start_send(item)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.0.try_poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.0.try_poll_close(cx)
+    }
+}
+
 /// The receiving end of a test channel.
-pub struct TestReceiver<I, E>(Receiver<Result<I, E>>);
+///
+/// A `TestReceiver` is `Send` whenever both `I` and `E` are `Send`, so it can be moved into a task
+/// on a multi-threaded executor. It is `Sync` if additionally `E` is `Sync`.
+pub struct TestReceiver<I, E> {
+    inner: ReceiverInner<Result<I, E>>,
+    closed: Arc<AtomicBool>,
+    dropped: Arc<AtomicWaker>,
+    disconnect_err: Option<E>,
+    rendezvous: Option<Arc<Rendezvous>>,
+}
 
 impl<I, E> TestReceiver<I, E> {
-    fn new(receiver: Receiver<Result<I, E>>) -> TestReceiver<I, E> {
-        TestReceiver(receiver)
+    /// Convert the errors sent over the channel with the given function, leaving items and the end
+    /// of the stream untouched.
+    ///
+    /// This also applies to the error emitted on a premature disconnect.
+    pub fn map_err<F, E2>(self, f: F) -> MapErr<Self, F>
+        where F: FnMut(E) -> E2
+    {
+        StreamExt::map_err(self, f)
+    }
+
+    /// Convert the items sent over the channel with the given function.
+    pub fn map_items<F, U>(self, f: F) -> Map<Self, F>
+        where F: FnMut(I) -> U
+    {
+        StreamExt::map(self, f)
+    }
+
+    /// Only yield the items sent over the channel for which the given predicate returns `true`.
+    pub fn filter_items<P>(self, pred: P) -> FilterItems<I, E, P>
+        where P: FnMut(&I) -> bool
+    {
+        FilterItems {
+            receiver: self,
+            pred,
+        }
+    }
+
+    /// Yield at most `n` items, ending afterwards.
+    pub fn take(self, n: u64) -> Take<Self> {
+        StreamExt::take(self, n)
     }
 }
 
@@ -78,24 +380,153 @@ impl<I, E> Stream for TestReceiver<I, E> {
     type Error = E;
 
     fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
-        match self.0.poll_next(cx) {
+        let poll = match self.inner {
+            ReceiverInner::Bounded(ref mut receiver) => receiver.poll_next(cx),
+            ReceiverInner::Unbounded(ref mut receiver) => receiver.poll_next(cx),
+        };
+        if let Ok(Async::Ready(Some(_))) = poll {
+            if let Some(ref rendezvous) = self.rendezvous {
+                rendezvous.take();
+            }
+        }
+        match poll {
             Ok(Async::Ready(Some(Ok(item)))) => Ok(Async::Ready(Some(item))),
             Ok(Async::Ready(Some(Err(err)))) => Err(err),
-            Ok(Async::Ready(None)) => Ok(Async::Ready(None)),
+            Ok(Async::Ready(None)) => {
+                if self.closed.load(Ordering::SeqCst) {
+                    return Ok(Async::Ready(None));
+                }
+                match self.disconnect_err.take() {
+                    Some(err) => Err(err),
+                    None => Ok(Async::Ready(None)),
+                }
+            }
             Ok(Async::Pending) => Ok(Async::Pending),
             Err(_) => unreachable!(),
         }
     }
 }
 
+/// The stream returned by `TestReceiver::filter_items`.
+pub struct FilterItems<I, E, P> {
+    receiver: TestReceiver<I, E>,
+    pred: P,
+}
+
+impl<I, E, P> Stream for FilterItems<I, E, P>
+    where P: FnMut(&I) -> bool
+{
+    type Item = I;
+    type Error = E;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.receiver.poll_next(cx)? {
+                Async::Ready(Some(item)) => {
+                    if (self.pred)(&item) {
+                        return Ok(Async::Ready(Some(item)));
+                    }
+                }
+                other => return Ok(other),
+            }
+        }
+    }
+}
+
+impl<I, E> Drop for TestReceiver<I, E> {
+    fn drop(&mut self) {
+        // Close the channel before waking, so that the woken sender observes it as closed.
+        match self.inner {
+            ReceiverInner::Bounded(ref mut receiver) => receiver.close(),
+            ReceiverInner::Unbounded(ref mut receiver) => receiver.close(),
+        }
+        self.dropped.wake();
+
+        // Unblock a rendezvous sender, so that it notices the disconnect instead of hanging.
+        if let Some(ref rendezvous) = self.rendezvous {
+            rendezvous.in_flight.store(0, Ordering::SeqCst);
+            rendezvous.waker.wake();
+        }
+    }
+}
+
+/// Create a pair of connected duplex endpoints, each direction being a test channel of the given
+/// capacity.
+///
+/// Everything sent into one endpoint is received by the other one. Closing an endpoint only closes
+/// its sending direction, so it can still receive from its peer.
+pub fn test_duplex<I, E>(capacity: usize) -> (TestDuplex<I, E>, TestDuplex<I, E>) {
+    let (sender_a, receiver_a) = test_channel(capacity);
+    let (sender_b, receiver_b) = test_channel(capacity);
+    (TestDuplex {
+         sender: sender_a,
+         receiver: receiver_b,
+     },
+     TestDuplex {
+         sender: sender_b,
+         receiver: receiver_a,
+     })
+}
+
+/// One endpoint of a duplex test connection, both a `TestSender` and a `TestReceiver`.
+///
+/// Like its halves, a `TestDuplex` is `Send` whenever both `I` and `E` are `Send`, and `Sync` if
+/// additionally `E` is `Sync`.
+pub struct TestDuplex<I, E> {
+    sender: TestSender<I, E>,
+    receiver: TestReceiver<I, E>,
+}
+
+impl<I, E> TestDuplex<I, E> {
+    /// Consume the endpoint, returning its sending and its receiving half.
+    pub fn split(self) -> (TestSender<I, E>, TestReceiver<I, E>) {
+        (self.sender, self.receiver)
+    }
+}
+
+impl<I, E> Sink for TestDuplex<I, E> {
+    type SinkItem = Result<I, E>;
+    type SinkError = Never;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.sender.poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.sender.start_send(item)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.sender.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        self.sender.poll_close(cx)
+    }
+}
+
+impl<I, E> Stream for TestDuplex<I, E> {
+    type Item = I;
+    type Error = E;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        self.receiver.poll_next(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::cell::Cell;
+
     use futures::{SinkExt, StreamExt, FutureExt};
     use futures::sink::close;
     use futures::stream::iter_ok;
     use futures::executor::block_on;
+    use futures::future::poll_fn;
+
+    use step::with_noop_context;
 
     #[test]
     fn it_works() {
@@ -117,4 +548,163 @@ mod tests {
 
         assert!(block_on(receive_stuff.join(send_stuff)).is_ok());
     }
+
+    #[test]
+    fn unbounded_never_blocks() {
+        let (mut sender, receiver) = unbounded_test_channel::<u8, Never>();
+
+        with_noop_context(|cx| {
+            for i in 0..100 {
+                assert!(sender.poll_ready(cx).unwrap().is_ready());
+                sender.start_send(Ok(i)).unwrap();
+            }
+        });
+        drop(sender);
+
+        assert_eq!(block_on(receiver.collect()).unwrap(), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn disconnect_errors_unless_closed() {
+        let (sender, receiver) = test_channel_erroring_on_disconnect::<u8, _>(1, "disconnected");
+        drop(sender);
+        let receiver = match block_on(receiver.into_future()) {
+            Err((err, receiver)) => {
+                assert_eq!(err, "disconnected");
+                receiver
+            }
+            Ok(_) => panic!("Disconnect was not reported"),
+        };
+        assert_eq!(block_on(receiver.collect()).unwrap(), vec![]);
+
+        let (sender, receiver) = test_channel_erroring_on_disconnect::<u8, _>(1, "disconnected");
+        block_on(close(sender)).unwrap();
+        assert_eq!(block_on(receiver.collect()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn try_send_reports_full() {
+        let (mut sender, receiver) = test_channel::<u8, Never>(2);
+        assert_eq!(sender.capacity(), 2);
+        assert!(sender.try_send(Ok(0)).is_ok());
+        assert!(sender.try_send(Ok(1)).is_ok());
+
+        let err = sender.try_send(Ok(2)).unwrap_err();
+        assert!(err.is_full());
+        assert_eq!(err.into_inner(), Ok(2));
+
+        drop(sender);
+        assert_eq!(block_on(receiver.collect()).unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn duplex_half_close() {
+        let (a, b) = test_duplex::<u8, Never>(2);
+        let a = block_on(a.send(Ok(0)).and_then(close)).unwrap();
+
+        let (b_sender, b_receiver) = b.split();
+        assert_eq!(block_on(b_receiver.collect()).unwrap(), vec![0]);
+
+        block_on(b_sender.send(Ok(1)).and_then(close)).unwrap();
+        assert_eq!(block_on(a.collect()).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn rendezvous_waits_for_receiver() {
+        let (mut sender, mut receiver) = test_channel::<u8, Never>(0);
+        assert_eq!(sender.capacity(), 0);
+
+        with_noop_context(|cx| {
+            assert!(sender.poll_ready(cx).unwrap().is_ready());
+            sender.start_send(Ok(0)).unwrap();
+            assert!(sender.poll_flush(cx).unwrap().is_pending());
+            assert!(sender.poll_ready(cx).unwrap().is_pending());
+
+            assert_eq!(receiver.poll_next(cx), Ok(Async::Ready(Some(0))));
+            assert!(sender.poll_flush(cx).unwrap().is_ready());
+        });
+
+        let send = sender.send(Ok(1)).and_then(|sender| sender.send(Ok(2))).and_then(close);
+        let (_, items) = block_on(send.join(receiver.collect())).unwrap();
+        assert_eq!(items, vec![1, 2]);
+    }
+
+    #[test]
+    fn sender_observes_dropped_receiver() {
+        let (sender, receiver) = test_channel::<u8, Never>(1);
+        with_noop_context(|cx| assert!(sender.poll_canceled(cx).is_pending()));
+        assert!(!sender.is_closed());
+
+        let canceled = poll_fn(|cx| Ok::<_, Never>(sender.poll_canceled(cx)));
+        let mut receiver = Some(receiver);
+        let drop_receiver = poll_fn(move |_| {
+                                        receiver.take();
+                                        Ok::<_, Never>(Async::Ready(()))
+                                    });
+        block_on(canceled.join(drop_receiver)).unwrap();
+        assert!(sender.is_closed());
+    }
+
+    #[test]
+    fn no_panic_sender_reports_disconnect() {
+        let (mut sender, receiver) = test_channel_no_panic::<u8, Never>(1);
+        drop(receiver);
+        with_noop_context(|cx| assert!(sender.poll_ready(cx).is_err()));
+    }
+
+    #[test]
+    fn receiver_adapters() {
+        let (mut sender, receiver) = test_channel::<u8, Never>(8);
+        for item in 0..8 {
+            sender.try_send(Ok(item)).unwrap();
+        }
+        let items = block_on(receiver.map_items(|item| item * 10).take(3).collect()).unwrap();
+        assert_eq!(items, vec![0, 10, 20]);
+
+        let (mut sender, receiver) = test_channel::<u8, Never>(8);
+        for item in 0..8 {
+            sender.try_send(Ok(item)).unwrap();
+        }
+        drop(sender);
+        let items = block_on(receiver.filter_items(|item| item % 3 == 0).collect()).unwrap();
+        assert_eq!(items, vec![0, 3, 6]);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Wrapped(u8);
+
+    #[test]
+    fn receiver_maps_errors() {
+        let (mut sender, receiver) = test_channel(4);
+        sender.try_send(Ok(0)).unwrap();
+        sender.try_send(Err(1)).unwrap();
+
+        let mut receiver = receiver.map_err(Wrapped);
+        with_noop_context(|cx| {
+            assert_eq!(receiver.poll_next(cx), Ok(Async::Ready(Some(0))));
+            assert_eq!(receiver.poll_next(cx), Err(Wrapped(1)));
+        });
+    }
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    fn assert_send_bounds<I: Send, E: Send>() {
+        assert_send::<TestSender<I, E>>();
+        assert_sync::<TestSender<I, E>>();
+        assert_send::<TestReceiver<I, E>>();
+        assert_send::<TestDuplex<I, E>>();
+    }
+
+    // The receiver stores the disconnect error, so sharing it requires `E: Sync`.
+    fn assert_sync_bounds<I: Send, E: Send + Sync>() {
+        assert_sync::<TestReceiver<I, E>>();
+        assert_sync::<TestDuplex<I, E>>();
+    }
+
+    #[test]
+    fn auto_traits() {
+        assert_send_bounds::<Cell<u8>, Cell<u8>>();
+        assert_sync_bounds::<Cell<u8>, u8>();
+    }
 }
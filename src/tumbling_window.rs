@@ -0,0 +1,200 @@
+use futures_core::{Stream, Poll, Async};
+use futures_core::task::Context;
+
+/// What a `TumblingWindow` does with items belonging to a window that has already been emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatePolicy {
+    /// Discard late items, only counting them.
+    Drop,
+    /// Collect late items, so that they can be inspected via `TumblingWindow::late_items`.
+    SideOutput,
+}
+
+/// A stream combinator grouping items into consecutive, non-overlapping windows.
+///
+/// The window of an item is given by `F`. A window is emitted together with its id once an item
+/// of a later window arrives, or when the inner stream ends. Items of earlier windows than the
+/// one currently being filled are late, and are handled according to the `LatePolicy`.
+///
+/// Alternatively, a `TumblingWindow` created via `by_count` groups items by their number instead:
+/// A window is emitted as soon as it holds a fixed number of items, its id being the number of
+/// windows emitted before it. In that mode, no item is ever late.
+pub struct TumblingWindow<S: Stream, F> {
+    inner: S,
+    window_fn: F,
+    late_policy: LatePolicy,
+    current: Option<(u64, Vec<S::Item>)>,
+    late: Vec<S::Item>,
+    dropped: usize,
+    done: bool,
+    // The number of items per window in count-based mode.
+    window_size: Option<usize>,
+    // The number of windows emitted in count-based mode.
+    emitted: u64,
+}
+
+impl<S, F> TumblingWindow<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> u64
+{
+    /// Create a new `TumblingWindow`, using `window_fn` to assign items to windows.
+    pub fn new(inner: S, window_fn: F, late_policy: LatePolicy) -> TumblingWindow<S, F> {
+        TumblingWindow {
+            inner,
+            window_fn,
+            late_policy,
+            current: None,
+            late: Vec::new(),
+            dropped: 0,
+            done: false,
+            window_size: None,
+            emitted: 0,
+        }
+    }
+
+    /// Return the late items collected so far under `LatePolicy::SideOutput`.
+    pub fn late_items(&self) -> &[S::Item] {
+        &self.late
+    }
+
+    /// Return how many late items have been discarded under `LatePolicy::Drop`.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    fn handle_late(&mut self, item: S::Item) {
+        match self.late_policy {
+            LatePolicy::Drop => self.dropped += 1,
+            LatePolicy::SideOutput => self.late.push(item),
+        }
+    }
+}
+
+fn no_window<T>(_: &T) -> u64 {
+    0
+}
+
+impl<S: Stream> TumblingWindow<S, fn(&S::Item) -> u64> {
+    /// Create a new `TumblingWindow` that emits a window after every `window_size` items.
+    ///
+    /// # Panics
+    /// Panics if `window_size` is 0.
+    pub fn by_count(inner: S, window_size: usize) -> TumblingWindow<S, fn(&S::Item) -> u64> {
+        if window_size == 0 {
+            panic!("TumblingWindow must have a window size greater than 0")
+        }
+
+        let window_fn = no_window as fn(&S::Item) -> u64;
+        let mut windows = TumblingWindow::new(inner, window_fn, LatePolicy::Drop);
+        windows.window_size = Some(window_size);
+        windows
+    }
+}
+
+impl<S, F> Stream for TumblingWindow<S, F>
+    where S: Stream,
+          F: FnMut(&S::Item) -> u64
+{
+    type Item = (u64, Vec<S::Item>);
+    type Error = S::Error;
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<Self::Item>, Self::Error> {
+        while !self.done {
+            match self.inner.poll_next(cx)? {
+                Async::Ready(Some(item)) => {
+                    if let Some(window_size) = self.window_size {
+                        let id = self.emitted;
+                        let full = {
+                            let window = self.current.get_or_insert_with(|| (id, Vec::new()));
+                            window.1.push(item);
+                            window.1.len() == window_size
+                        };
+                        if full {
+                            self.emitted += 1;
+                            return Ok(Async::Ready(self.current.take()));
+                        }
+                        continue;
+                    }
+
+                    let id = (self.window_fn)(&item);
+                    let current_id = self.current.as_ref().map(|&(current_id, _)| current_id);
+
+                    match current_id {
+                        Some(current_id) if id < current_id => self.handle_late(item),
+                        Some(current_id) if id == current_id => {
+                            if let Some((_, ref mut window)) = self.current {
+                                window.push(item);
+                            }
+                        }
+                        Some(_) => {
+                            let emitted = self.current.replace((id, vec![item]));
+                            return Ok(Async::Ready(emitted));
+                        }
+                        None => self.current = Some((id, vec![item])),
+                    }
+                }
+                Async::Ready(None) => self.done = true,
+                Async::Pending => return Ok(Async::Pending),
+            }
+        }
+
+        Ok(Async::Ready(self.current.take()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+    use futures::stream::iter_ok;
+    use futures::executor::block_on;
+    use futures::never::Never;
+
+    use step::with_noop_context;
+
+    fn items() -> Vec<u64> {
+        vec![1, 2, 11, 5, 12, 21]
+    }
+
+    #[test]
+    fn late_item_to_side_output() {
+        let mut windows = TumblingWindow::new(iter_ok::<_, Never>(items()),
+                                              |&item| item / 10,
+                                              LatePolicy::SideOutput);
+
+        assert_eq!(block_on((&mut windows).collect()).unwrap(),
+                   vec![(0, vec![1, 2]), (1, vec![11, 12]), (2, vec![21])]);
+        assert_eq!(windows.late_items(), &[5]);
+        assert_eq!(windows.dropped(), 0);
+    }
+
+    #[test]
+    fn late_item_dropped() {
+        let mut windows = TumblingWindow::new(iter_ok::<_, Never>(items()),
+                                              |&item| item / 10,
+                                              LatePolicy::Drop);
+
+        assert_eq!(block_on((&mut windows).collect()).unwrap(),
+                   vec![(0, vec![1, 2]), (1, vec![11, 12]), (2, vec![21])]);
+        assert!(windows.late_items().is_empty());
+        assert_eq!(windows.dropped(), 1);
+    }
+
+    #[test]
+    fn count_based_windows() {
+        let mut windows = TumblingWindow::by_count(iter_ok::<_, Never>(items()), 4);
+
+        with_noop_context(|cx| {
+            assert_eq!(windows.poll_next(cx), Ok(Async::Ready(Some((0, vec![1, 2, 11, 5])))));
+        });
+        assert_eq!(block_on(windows.collect()).unwrap(), vec![(1, vec![12, 21])]);
+    }
+
+    #[test]
+    fn count_based_windows_of_one() {
+        let windows = TumblingWindow::by_count(iter_ok::<_, Never>(vec![7, 8]), 1);
+        assert_eq!(block_on(windows.collect()).unwrap(),
+                   vec![(0, vec![7]), (1, vec![8])]);
+    }
+}
@@ -0,0 +1,71 @@
+use futures_core::{Poll, Async, Never};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink that stores all items it receives in a `Vec`.
+///
+/// Flushing and closing always complete immediately.
+pub struct VecSink<T> {
+    items: Vec<T>,
+}
+
+impl<T> VecSink<T> {
+    /// Create a new, empty `VecSink`.
+    pub fn new() -> VecSink<T> {
+        VecSink { items: Vec::new() }
+    }
+
+    /// Return the items received so far, in the order they were sent.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Consume the sink, returning the items it received.
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Default for VecSink<T> {
+    fn default() -> VecSink<T> {
+        VecSink::new()
+    }
+}
+
+impl<T> Sink for VecSink<T> {
+    type SinkItem = T;
+    type SinkError = Never;
+
+    fn poll_ready(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn poll_flush(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+
+    fn poll_close(&mut self, _: &mut Context) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+
+    use send_many::SendMany;
+
+    #[test]
+    fn stores_items() {
+        let sink = block_on(SendMany::new(VecSink::new(), vec![0, 1, 2])).unwrap();
+        assert_eq!(sink.items(), &[0, 1, 2]);
+        assert_eq!(sink.into_vec(), vec![0, 1, 2]);
+    }
+}
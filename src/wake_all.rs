@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures_core::task::{Wake, Waker};
+
+/// A waker that wakes all registered wakers.
+///
+/// Polling a shared resource with this waker ensures that every task waiting on the resource gets
+/// notified, rather than only the one that polled it most recently.
+#[derive(Default)]
+pub struct WakeAll(Mutex<HashMap<usize, Waker>>);
+
+impl WakeAll {
+    /// Register the waker of the task with the given id, replacing any previous one.
+    pub fn register(&self, id: usize, waker: Waker) {
+        self.0.lock().unwrap().insert(id, waker);
+    }
+}
+
+impl Wake for WakeAll {
+    fn wake(arc_self: &Arc<WakeAll>) {
+        for (_, waker) in arc_self.0.lock().unwrap().drain() {
+            waker.wake();
+        }
+    }
+}
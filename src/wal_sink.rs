@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+
+use futures_core::{Poll, Async};
+use futures_core::task::Context;
+use futures_sink::Sink;
+
+/// A sink wrapper simulating a write-ahead log with replay after a restart.
+///
+/// Every item sent is appended to an in-memory log before being forwarded to the inner sink.
+/// `checkpoint` marks all logged items as durable, `reopen` simulates a crash and restart by
+/// replacing the inner sink with a fresh one from the factory and replaying all items logged
+/// since the last checkpoint into it. The replay happens before anything else the next time the
+/// sink is polled.
+pub struct WalSink<S: Sink, F> {
+    inner: S,
+    factory: F,
+    wal: Vec<S::SinkItem>,
+    replay: VecDeque<S::SinkItem>,
+}
+
+impl<S, F> WalSink<S, F>
+    where S: Sink,
+          S::SinkItem: Clone,
+          F: FnMut() -> S
+{
+    /// Create a new `WalSink`, using `inner_factory` to create the inner sink.
+    pub fn new(mut inner_factory: F) -> WalSink<S, F> {
+        WalSink {
+            inner: inner_factory(),
+            factory: inner_factory,
+            wal: Vec::new(),
+            replay: VecDeque::new(),
+        }
+    }
+
+    /// Mark all items logged so far as durable, so that they are not replayed on `reopen`.
+    pub fn checkpoint(&mut self) {
+        self.wal.clear();
+    }
+
+    /// Simulate a restart: Discard the inner sink, create a fresh one and replay all items logged
+    /// since the last checkpoint into it.
+    pub fn reopen(&mut self) {
+        self.inner = (self.factory)();
+        self.replay = self.wal.iter().cloned().collect();
+    }
+
+    /// Return the items that have been logged since the last checkpoint.
+    pub fn uncheckpointed(&self) -> &[S::SinkItem] {
+        &self.wal
+    }
+
+    /// Get a reference to the current inner sink.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    fn poll_replay(&mut self, cx: &mut Context) -> Poll<(), S::SinkError> {
+        while !self.replay.is_empty() {
+            if let Async::Pending = self.inner.poll_ready(cx)? {
+                return Ok(Async::Pending);
+            }
+            let item = self.replay.pop_front().unwrap();
+            self.inner.start_send(item)?;
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<S, F> Sink for WalSink<S, F>
+    where S: Sink,
+          S::SinkItem: Clone,
+          F: FnMut() -> S
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.poll_replay(cx)? {
+            return Ok(Async::Pending);
+        }
+        self.inner.poll_ready(cx)
+    }
+
+    fn start_send(&mut self, item: Self::SinkItem) -> Result<(), Self::SinkError> {
+        self.wal.push(item.clone());
+        self.inner.start_send(item)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.poll_replay(cx)? {
+            return Ok(Async::Pending);
+        }
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context) -> Poll<(), Self::SinkError> {
+        if let Async::Pending = self.poll_replay(cx)? {
+            return Ok(Async::Pending);
+        }
+        self.inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::{SinkExt, FutureExt};
+    use futures::sink::flush;
+    use futures::executor::block_on;
+
+    #[test]
+    fn replays_uncheckpointed_items_once() {
+        let sink = WalSink::new(Vec::new);
+        let mut sink = block_on(sink.send(0).and_then(|sink| sink.send(1))).unwrap();
+        sink.checkpoint();
+
+        let mut sink = block_on(sink.send(2).and_then(|sink| sink.send(3))).unwrap();
+        assert_eq!(sink.get_ref(), &vec![0, 1, 2, 3]);
+
+        sink.reopen();
+        let sink = block_on(flush(sink).and_then(|sink| sink.send(4))).unwrap();
+        assert_eq!(sink.get_ref(), &vec![2, 3, 4]);
+        assert_eq!(sink.uncheckpointed(), &[2, 3, 4]);
+    }
+}